@@ -0,0 +1,192 @@
+use std::path::Path;
+
+use super::{load, load_guide, Game, Hand, Round, Strategy};
+
+/// The best responses to the opponent column of a guide, and how they
+/// compare with the guide's own strategies.
+#[derive(Debug)]
+pub(super) struct CounterStrategy {
+    /// How often the opponent plays each hand.
+    frequencies: Vec<f64>,
+    /// How often the opponent plays each hand, given the hand they played in
+    /// the previous round.
+    transitions: Vec<Vec<f64>>,
+    /// The single hand that maximises the expected score against [frequencies].
+    overall_policy: Hand,
+    /// The hand that maximises the expected score after the opponent played
+    /// each hand, falling back to [overall_policy] in the first round.
+    conditional_policy: Vec<Hand>,
+    overall_score: u32,
+    conditional_score: u32,
+    original_score: u32,
+    new_score: u32,
+}
+
+impl CounterStrategy {
+    /// How much the best policy beats [Strategy::Original] by.
+    fn margin_over_original(&self) -> i64 {
+        self.best_score() as i64 - self.original_score as i64
+    }
+
+    /// How much the best policy beats [Strategy::New] by.
+    fn margin_over_new(&self) -> i64 {
+        self.best_score() as i64 - self.new_score as i64
+    }
+
+    fn best_score(&self) -> u32 {
+        self.overall_score.max(self.conditional_score)
+    }
+}
+
+fn distribution(counts: &[u32]) -> Vec<f64> {
+    let total: u32 = counts.iter().sum();
+    counts
+        .iter()
+        .map(|&count| count as f64 / total as f64)
+        .collect()
+}
+
+fn expected_score(game: &Game, you: Hand, distribution: &[f64]) -> f64 {
+    distribution
+        .iter()
+        .enumerate()
+        .map(|(opponent, p)| {
+            p * Round::from_original_strategy(game, you, Hand(opponent)).score(game) as f64
+        })
+        .sum()
+}
+
+fn best_response(game: &Game, distribution: &[f64]) -> Hand {
+    let mut best = Hand(0);
+    let mut best_score = f64::MIN;
+    for you in (0..game.num_hands()).map(Hand) {
+        let score = expected_score(game, you, distribution);
+        if score > best_score {
+            best = you;
+            best_score = score;
+        }
+    }
+    best
+}
+
+fn analyse(game: &Game, path: &Path) -> CounterStrategy {
+    let opponents: Vec<Hand> = load_guide(path)
+        .iter()
+        .map(|(opponent, _)| game.hand_from_unencrypted(opponent))
+        .collect();
+    let n = game.num_hands();
+
+    let mut counts = vec![0; n];
+    let mut transition_counts = vec![vec![0; n]; n];
+    for (idx, opponent) in opponents.iter().enumerate() {
+        counts[opponent.0] += 1;
+        if idx > 0 {
+            transition_counts[opponents[idx - 1].0][opponent.0] += 1;
+        }
+    }
+    let frequencies = distribution(&counts);
+    let transitions: Vec<Vec<f64>> = transition_counts
+        .iter()
+        .map(|counts| {
+            if counts.iter().sum::<u32>() == 0 {
+                frequencies.clone()
+            } else {
+                distribution(counts)
+            }
+        })
+        .collect();
+
+    let overall_policy = best_response(game, &frequencies);
+    let conditional_policy: Vec<Hand> = transitions
+        .iter()
+        .map(|distribution| best_response(game, distribution))
+        .collect();
+
+    let play =
+        |you: Hand, opponent: Hand| Round::from_original_strategy(game, you, opponent).score(game);
+    let overall_score = opponents
+        .iter()
+        .map(|&opponent| play(overall_policy, opponent))
+        .sum();
+    let conditional_score = opponents
+        .iter()
+        .enumerate()
+        .map(|(idx, &opponent)| match idx {
+            0 => play(overall_policy, opponent),
+            _ => play(conditional_policy[opponents[idx - 1].0], opponent),
+        })
+        .sum();
+    let total = |strategy: Strategy| {
+        load(path, game, strategy)
+            .iter()
+            .map(|round| round.score(game))
+            .sum()
+    };
+
+    CounterStrategy {
+        frequencies,
+        transitions,
+        overall_policy,
+        conditional_policy,
+        overall_score,
+        conditional_score,
+        original_score: total(Strategy::Original),
+        new_score: total(Strategy::New),
+    }
+}
+
+/// Prints the optimal counter-strategy to the opponent column of the guide
+/// at [path].
+#[allow(dead_code)]
+pub fn report(path: &Path) {
+    let game = Game::rock_paper_scissors();
+    let counter = analyse(&game, path);
+    for (hand, p) in counter.frequencies.iter().enumerate() {
+        println!("Opponent plays {}: {:.3}", game.hands[hand], p);
+    }
+    for (prev, distribution) in counter.transitions.iter().enumerate() {
+        println!(
+            "After {}: {:?}, respond with {}",
+            game.hands[prev], distribution, game.hands[counter.conditional_policy[prev].0]
+        );
+    }
+    println!(
+        "Always {}: {}",
+        game.hands[counter.overall_policy.0], counter.overall_score
+    );
+    println!("Conditional: {}", counter.conditional_score);
+    println!(
+        "Original: {} ({:+}), New: {} ({:+})",
+        counter.original_score,
+        counter.margin_over_original(),
+        counter.new_score,
+        counter.margin_over_new()
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let game = Game::rock_paper_scissors();
+        let counter = analyse(&game, Path::new("src/dec02/example.txt"));
+        assert_eq!(counter.overall_policy, Hand(2));
+        assert_eq!(counter.overall_score, 18);
+        assert_eq!(counter.conditional_score, 19);
+        assert_eq!(counter.original_score, 15);
+        assert_eq!(counter.new_score, 12);
+        assert_eq!(counter.margin_over_original(), 4);
+        assert_eq!(counter.margin_over_new(), 7);
+    }
+
+    #[test]
+    fn test_conditional_beats_overall() {
+        let game = Game::rock_paper_scissors();
+        let counter = analyse(&game, Path::new("src/dec02/testdata.txt"));
+        assert!(counter.conditional_score >= counter.overall_score);
+        assert!(counter.margin_over_original() > 0);
+        assert!(counter.margin_over_new() > 0);
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::Path;
+
+use super::{load_guide, Game, Hand, Round, RoundResult};
+
+/// What a symbol in our column of the strategy guide could stand for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Meaning {
+    Hand(Hand),
+    Result(RoundResult),
+}
+
+/// An assignment of a distinct [Meaning] to each symbol in our column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Decoding(Vec<(String, Meaning)>);
+
+impl Decoding {
+    fn meaning(&self, symbol: &str) -> Meaning {
+        match self.0.iter().find(|(s, _)| s == symbol) {
+            Some((_, meaning)) => *meaning,
+            None => panic!("Unexpected symbol: {}", symbol),
+        }
+    }
+
+    fn round(&self, game: &Game, opponent: Hand, symbol: &str) -> Round {
+        match self.meaning(symbol) {
+            Meaning::Hand(you) => Round::from_original_strategy(game, you, opponent),
+            Meaning::Result(result) => Round::from_modified_strategy(game, opponent, result),
+        }
+    }
+
+    fn score(&self, game: &Game, guide: &[(String, String)]) -> u32 {
+        guide
+            .iter()
+            .map(|(opponent, ours)| {
+                self.round(game, game.hand_from_unencrypted(opponent), ours)
+                    .score(game)
+            })
+            .sum()
+    }
+
+    /// Renders the decoding as e.g. `X=Rock Y=Draw Z=Win`.
+    pub(super) fn describe(&self, game: &Game) -> String {
+        self.0
+            .iter()
+            .map(|(symbol, meaning)| match meaning {
+                Meaning::Hand(hand) => format!("{}={}", symbol, game.hands[hand.0]),
+                Meaning::Result(result) => format!("{}={:?}", symbol, result),
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+/// Every way of assigning distinct meanings to the symbols in our column of
+/// [guide]: all hands, all results, or a mix of both.
+fn candidates(game: &Game, guide: &[(String, String)]) -> Vec<Decoding> {
+    let mut symbols: Vec<String> = guide.iter().map(|(_, ours)| ours.clone()).collect();
+    symbols.sort();
+    symbols.dedup();
+    let mut meanings: Vec<Meaning> = (0..game.num_hands())
+        .map(|h| Meaning::Hand(Hand(h)))
+        .collect();
+    meanings.extend([RoundResult::Loss, RoundResult::Draw, RoundResult::Win].map(Meaning::Result));
+
+    fn assign(
+        symbols: &[String],
+        meanings: &[Meaning],
+        chosen: &mut Vec<Meaning>,
+        used: &mut Vec<bool>,
+        decodings: &mut Vec<Decoding>,
+    ) {
+        if chosen.len() == symbols.len() {
+            decodings.push(Decoding(
+                symbols
+                    .iter()
+                    .cloned()
+                    .zip(chosen.iter().copied())
+                    .collect(),
+            ));
+            return;
+        }
+        for (idx, meaning) in meanings.iter().enumerate() {
+            if used[idx] {
+                continue;
+            }
+            used[idx] = true;
+            chosen.push(*meaning);
+            assign(symbols, meanings, chosen, used, decodings);
+            chosen.pop();
+            used[idx] = false;
+        }
+    }
+
+    let mut decodings = vec![];
+    assign(
+        &symbols,
+        &meanings,
+        &mut vec![],
+        &mut vec![false; meanings.len()],
+        &mut decodings,
+    );
+    decodings
+}
+
+/// Scores [guide] under every candidate decoding.
+pub(super) fn score_decodings(game: &Game, guide: &[(String, String)]) -> Vec<(Decoding, u32)> {
+    candidates(game, guide)
+        .into_iter()
+        .map(|decoding| {
+            let score = decoding.score(game, guide);
+            (decoding, score)
+        })
+        .collect()
+}
+
+/// The decodings under which [guide] totals exactly [target].
+pub(super) fn infer_decodings(
+    game: &Game,
+    guide: &[(String, String)],
+    target: u32,
+) -> Vec<Decoding> {
+    score_decodings(game, guide)
+        .into_iter()
+        .filter(|(_, score)| *score == target)
+        .map(|(decoding, _)| decoding)
+        .collect()
+}
+
+/// Describes every decoding under which the guide at [path] totals [target].
+#[allow(dead_code)]
+pub fn infer(path: &Path, target: u32) -> Vec<String> {
+    let game = Game::rock_paper_scissors();
+    infer_decodings(&game, &load_guide(path), target)
+        .iter()
+        .map(|decoding| decoding.describe(&game))
+        .collect()
+}
+
+/// Prints the score of every decoding of the guide at [path], marking the
+/// ones that total [target].
+#[allow(dead_code)]
+pub fn report(path: &Path, target: u32) {
+    let game = Game::rock_paper_scissors();
+    let guide = load_guide(path);
+    for (decoding, score) in score_decodings(&game, &guide) {
+        let marker = if score == target { "*" } else { " " };
+        println!("{} {:>8} {}", marker, score, decoding.describe(&game));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let game = Game::rock_paper_scissors();
+        let guide = load_guide(Path::new("src/dec02/example.txt"));
+        let decodings = candidates(&game, &guide);
+        assert_eq!(decodings.len(), 6 * 5 * 4);
+        let all_hands = decodings
+            .iter()
+            .filter(|d| d.0.iter().all(|(_, m)| matches!(m, Meaning::Hand(_))))
+            .count();
+        assert_eq!(all_hands, 6);
+        let all_results = decodings
+            .iter()
+            .filter(|d| d.0.iter().all(|(_, m)| matches!(m, Meaning::Result(_))))
+            .count();
+        assert_eq!(all_results, 6);
+    }
+
+    #[test]
+    fn test_infer_decodings() {
+        let game = Game::rock_paper_scissors();
+        let guide = load_guide(Path::new("src/dec02/testdata.txt"));
+        let original = infer_decodings(&game, &guide, 8933);
+        assert!(original
+            .iter()
+            .any(|d| d.describe(&game) == "X=Rock Y=Paper Z=Scissors"));
+        let new = infer_decodings(&game, &guide, 11998);
+        assert!(new
+            .iter()
+            .any(|d| d.describe(&game) == "X=Loss Y=Draw Z=Win"));
+    }
+}
@@ -0,0 +1,195 @@
+use std::{fmt::Write, path::Path};
+
+use super::{load_guide, Game, Hand, Round, RoundResult, Strategy};
+
+#[derive(Debug, PartialEq, Eq)]
+enum GuideError {
+    /// No guide totals the requested score; these are the lowest and highest
+    /// totals any guide can reach.
+    Unreachable { min: u32, max: u32 },
+}
+
+/// A fixed-width set of totals, one bit per total.
+struct Totals(Vec<u64>);
+
+impl Totals {
+    fn new(width: usize) -> Self {
+        Totals(vec![0; width / 64 + 1])
+    }
+
+    fn insert(&mut self, total: usize) {
+        self.0[total / 64] |= 1 << (total % 64);
+    }
+
+    fn contains(&self, total: usize) -> bool {
+        self.0[total / 64] & (1 << (total % 64)) != 0
+    }
+
+    /// Adds every total in [other], increased by [shift].
+    fn insert_shifted(&mut self, other: &Totals, shift: usize) {
+        let (words, bits) = (shift / 64, shift % 64);
+        for idx in (words..self.0.len()).rev() {
+            let src = idx - words;
+            let mut word = other.0[src] << bits;
+            if bits > 0 && src > 0 {
+                word |= other.0[src - 1] >> (64 - bits);
+            }
+            self.0[idx] |= word;
+        }
+    }
+}
+
+/// The symbols we could write against [opponent], with the score each gives.
+fn options(game: &Game, opponent: Hand, strategy: Strategy) -> Vec<(String, u32)> {
+    match strategy {
+        Strategy::Original => (0..game.num_hands())
+            .map(|you| {
+                let round = Round::from_original_strategy(game, Hand(you), opponent);
+                (game.your_symbols[you].clone(), round.score(game))
+            })
+            .collect(),
+        Strategy::New => [RoundResult::Loss, RoundResult::Draw, RoundResult::Win]
+            .iter()
+            .zip(game.result_symbols.iter())
+            .map(|(&result, symbol)| {
+                let round = Round::from_modified_strategy(game, opponent, result);
+                (symbol.clone(), round.score(game))
+            })
+            .collect(),
+    }
+}
+
+/// Builds a guide against [opponents] that totals exactly [target] when read
+/// with [strategy].
+fn generate(
+    game: &Game,
+    opponents: &[Hand],
+    strategy: Strategy,
+    target: u32,
+) -> Result<Vec<(String, String)>, GuideError> {
+    let options: Vec<Vec<(String, u32)>> = opponents
+        .iter()
+        .map(|&opponent| options(game, opponent, strategy))
+        .collect();
+    let bound = |pick: fn(u32, u32) -> u32| -> u32 {
+        options
+            .iter()
+            .map(|round| round.iter().map(|(_, score)| *score).reduce(pick).unwrap())
+            .sum()
+    };
+    let (min, max) = (bound(u32::min), bound(u32::max));
+    if target < min || target > max {
+        return Err(GuideError::Unreachable { min, max });
+    }
+
+    // reachable[i] holds every total the first i rounds can add up to.
+    let mut reachable = vec![Totals::new(max as usize)];
+    reachable[0].insert(0);
+    for round in &options {
+        let mut next = Totals::new(max as usize);
+        for (_, score) in round {
+            next.insert_shifted(reachable.last().unwrap(), *score as usize);
+        }
+        reachable.push(next);
+    }
+    if !reachable.last().unwrap().contains(target as usize) {
+        return Err(GuideError::Unreachable { min, max });
+    }
+
+    let mut remaining = target;
+    let mut symbols = Vec::with_capacity(opponents.len());
+    for (idx, round) in options.iter().enumerate().rev() {
+        let (symbol, score) = round
+            .iter()
+            .find(|(_, score)| {
+                *score <= remaining && reachable[idx].contains((remaining - score) as usize)
+            })
+            .unwrap();
+        symbols.push(symbol.clone());
+        remaining -= score;
+    }
+    symbols.reverse();
+    Ok(opponents
+        .iter()
+        .zip(symbols)
+        .map(|(opponent, symbol)| (game.opponent_symbols[opponent.0].clone(), symbol))
+        .collect())
+}
+
+/// Renders a guide in the puzzle's input format.
+fn render_guide(guide: &[(String, String)]) -> String {
+    guide
+        .iter()
+        .fold(String::new(), |mut accum, (opponent, ours)| {
+            writeln!(accum, "{} {}", opponent, ours).unwrap();
+            accum
+        })
+}
+
+/// Writes a guide against the opponent column of the guide at [path] that
+/// totals [target] under [strategy], or reports the achievable range.
+#[allow(dead_code)]
+pub fn fixture(path: &Path, strategy: Strategy, target: u32) -> String {
+    let game = Game::rock_paper_scissors();
+    let opponents: Vec<Hand> = load_guide(path)
+        .iter()
+        .map(|(opponent, _)| game.hand_from_unencrypted(opponent))
+        .collect();
+    match generate(&game, &opponents, strategy, target) {
+        Ok(guide) => render_guide(&guide),
+        Err(GuideError::Unreachable { min, max }) => {
+            format!(
+                "{} is unreachable, totals range from {} to {}",
+                target, min, max
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::decode;
+    use super::*;
+
+    fn opponents(game: &Game) -> Vec<Hand> {
+        load_guide(Path::new("src/dec02/testdata.txt"))
+            .iter()
+            .map(|(opponent, _)| game.hand_from_unencrypted(opponent))
+            .collect()
+    }
+
+    #[test]
+    fn test_generate() {
+        let game = Game::rock_paper_scissors();
+        let opponents = opponents(&game);
+        for strategy in [Strategy::Original, Strategy::New] {
+            for target in [8933, 10_000, 11998] {
+                let guide = generate(&game, &opponents, strategy, target).unwrap();
+                let total: u32 = decode(&game, &guide, strategy)
+                    .iter()
+                    .map(|round| round.score(&game))
+                    .sum();
+                assert_eq!(total, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let game = Game::rock_paper_scissors();
+        let opponents = [Hand(0), Hand(1), Hand(2)];
+        assert_eq!(
+            generate(&game, &opponents, Strategy::Original, 100),
+            Err(GuideError::Unreachable { min: 6, max: 24 })
+        );
+        // A lone Rock can only score 3, 4 or 8 under the original strategy.
+        assert_eq!(
+            generate(&game, &opponents[..1], Strategy::Original, 5),
+            Err(GuideError::Unreachable { min: 3, max: 8 })
+        );
+        assert_eq!(
+            render_guide(&generate(&game, &opponents[..1], Strategy::Original, 8).unwrap()),
+            "A Y\n"
+        );
+    }
+}
@@ -1,11 +1,17 @@
 use std::{fs, path::Path};
 
-enum Hand {
-    Rock,
-    Paper,
-    Scissors,
-}
+use derive_builder::Builder;
+
+mod counter;
+mod decoding;
+mod generator;
+mod tournament;
+
+/// A hand, identified by its position in the game's dominance cycle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct Hand(usize);
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum RoundResult {
     Win,
     Loss,
@@ -13,123 +19,239 @@ enum RoundResult {
 }
 
 impl RoundResult {
-    fn from_string(s: &str) -> Self {
-        match s {
-            "X" => RoundResult::Loss,
-            "Y" => RoundResult::Draw,
-            "Z" => RoundResult::Win,
-            _ => {
-                panic!("Unexpected hand: {}", s);
+    /// The result from the other player's point of view.
+    fn reverse(&self) -> Self {
+        match self {
+            RoundResult::Win => RoundResult::Loss,
+            RoundResult::Loss => RoundResult::Win,
+            RoundResult::Draw => RoundResult::Draw,
+        }
+    }
+}
+
+/// A cyclic game such as Rock-Paper-Scissors.
+///
+/// The hands are listed in cyclic order: with `n` hands, each hand beats the
+/// `(n - 1) / 2` hands before it in the cycle and loses to the `(n - 1) / 2`
+/// hands after it.
+#[derive(Debug, Clone, Builder)]
+#[builder(build_fn(validate = "Self::validate"))]
+struct Game {
+    hands: Vec<String>,
+    /// Symbols the opponent's column uses for each hand.
+    opponent_symbols: Vec<String>,
+    /// Symbols our column uses for each hand under [Strategy::Original].
+    your_symbols: Vec<String>,
+    /// Symbols our column uses for a loss, draw and win under [Strategy::New].
+    result_symbols: [String; 3],
+    hand_scores: Vec<u32>,
+    /// Scores for a loss, draw and win.
+    outcome_scores: [u32; 3],
+}
+
+impl GameBuilder {
+    fn validate(&self) -> Result<(), String> {
+        let n = match &self.hands {
+            Some(hands) if hands.len() % 2 == 1 => hands.len(),
+            Some(hands) => {
+                return Err(format!(
+                    "expected an odd number of hands, got {}",
+                    hands.len()
+                ))
+            }
+            None => return Ok(()),
+        };
+        for (name, len) in [
+            (
+                "opponent_symbols",
+                self.opponent_symbols.as_ref().map(Vec::len),
+            ),
+            ("your_symbols", self.your_symbols.as_ref().map(Vec::len)),
+            ("hand_scores", self.hand_scores.as_ref().map(Vec::len)),
+        ] {
+            if let Some(len) = len {
+                if len != n {
+                    return Err(format!("expected {} {}, got {}", n, name, len));
+                }
             }
         }
+        // Decoding takes the first match, so a repeated symbol would hide
+        // every hand or result after the first that uses it.
+        for (name, symbols) in [
+            ("opponent_symbols", self.opponent_symbols.as_deref()),
+            ("your_symbols", self.your_symbols.as_deref()),
+            (
+                "result_symbols",
+                self.result_symbols.as_ref().map(|s| &s[..]),
+            ),
+        ] {
+            if let Some(symbols) = symbols {
+                for (idx, symbol) in symbols.iter().enumerate() {
+                    if symbols[..idx].contains(symbol) {
+                        return Err(format!("{} repeats {:?}", name, symbol));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    /// A game over [hands] using the puzzle's conventions: the opponent plays
+    /// `A`, `B`, ..., we play the last `n` letters up to `Z`, hands score
+    /// `1..=n` and a loss, draw and win score 0, 3 and 6. There are only
+    /// enough letters for 26 hands.
+    fn cyclic(hands: &[&str]) -> Result<Self, GameBuilderError> {
+        let n = hands.len();
+        let letters: Vec<String> = ('A'..='Z').map(|c| c.to_string()).collect();
+        GameBuilder::default()
+            .hands(hands.iter().map(|h| h.to_string()).collect())
+            .opponent_symbols(letters.iter().take(n).cloned().collect())
+            .your_symbols(letters[letters.len().saturating_sub(n)..].to_vec())
+            .result_symbols(["X".to_string(), "Y".to_string(), "Z".to_string()])
+            .hand_scores((1..=n as u32).collect())
+            .outcome_scores([0, 3, 6])
+            .build()
+    }
+
+    fn rock_paper_scissors() -> Self {
+        Game::cyclic(&["Rock", "Paper", "Scissors"]).unwrap()
+    }
+
+    #[allow(dead_code)]
+    fn rock_paper_scissors_lizard_spock() -> Self {
+        Game::cyclic(&["Rock", "Spock", "Paper", "Lizard", "Scissors"]).unwrap()
+    }
+
+    #[allow(dead_code)]
+    fn rock_paper_scissors_7() -> Self {
+        Game::cyclic(&[
+            "Water", "Air", "Paper", "Sponge", "Scissors", "Fire", "Rock",
+        ])
+        .unwrap()
+    }
+
+    fn num_hands(&self) -> usize {
+        self.hands.len()
+    }
+
+    fn play(&self, you: Hand, opponent: Hand) -> RoundResult {
+        let n = self.num_hands();
+        match (you.0 + n - opponent.0) % n {
+            0 => RoundResult::Draw,
+            d if d <= n / 2 => RoundResult::Win,
+            _ => RoundResult::Loss,
+        }
+    }
+
+    /// The hand to play against [opponent] to get [result]. When several
+    /// hands would do, picks the one adjacent to [opponent] in the cycle.
+    fn respond(&self, opponent: Hand, result: RoundResult) -> Hand {
+        let n = self.num_hands();
+        match result {
+            RoundResult::Draw => opponent,
+            RoundResult::Win => Hand((opponent.0 + 1) % n),
+            RoundResult::Loss => Hand((opponent.0 + n - 1) % n),
+        }
+    }
+
+    fn hand_from_unencrypted(&self, s: &str) -> Hand {
+        match self.opponent_symbols.iter().position(|sym| sym == s) {
+            Some(idx) => Hand(idx),
+            None => panic!("Unexpected hand: {}", s),
+        }
+    }
+
+    fn hand_from_encrypted_strategy(&self, s: &str) -> Hand {
+        match self.your_symbols.iter().position(|sym| sym == s) {
+            Some(idx) => Hand(idx),
+            None => panic!("Unexpected hand: {}", s),
+        }
+    }
+
+    fn result_from_string(&self, s: &str) -> RoundResult {
+        match self.result_symbols.iter().position(|sym| sym == s) {
+            Some(0) => RoundResult::Loss,
+            Some(1) => RoundResult::Draw,
+            Some(2) => RoundResult::Win,
+            _ => panic!("Unexpected result: {}", s),
+        }
     }
 }
 
 struct Round {
     you: Hand,
+    opponent: Hand,
     result: RoundResult,
 }
 
+#[derive(Copy, Clone, Debug)]
 enum Strategy {
     Original,
     New,
 }
 
-impl Hand {
-    fn from_unencrypted(s: &str) -> Self {
-        match s {
-            "A" => Hand::Rock,
-            "B" => Hand::Paper,
-            "C" => Hand::Scissors,
-            _ => {
-                panic!("Unexpected hand: {}", s);
-            }
+impl Round {
+    fn from_original_strategy(game: &Game, you: Hand, opponent: Hand) -> Self {
+        Round {
+            you,
+            opponent,
+            result: game.play(you, opponent),
         }
     }
 
-    fn from_encrypted_strategy(s: &str) -> Self {
-        match s {
-            "X" => Hand::Rock,
-            "Y" => Hand::Paper,
-            "Z" => Hand::Scissors,
-            _ => {
-                panic!("Unexpected hand: {}", s);
-            }
+    fn from_modified_strategy(game: &Game, opponent: Hand, result: RoundResult) -> Self {
+        Round {
+            you: game.respond(opponent, result),
+            opponent,
+            result,
         }
     }
-}
 
-impl Round {
-    fn from_original_strategy(you: Hand, opponent: Hand) -> Self {
-        let result = match opponent {
-            Hand::Rock => match you {
-                Hand::Rock => RoundResult::Draw,
-                Hand::Paper => RoundResult::Win,
-                Hand::Scissors => RoundResult::Loss,
-            },
-            Hand::Paper => match you {
-                Hand::Rock => RoundResult::Loss,
-                Hand::Paper => RoundResult::Draw,
-                Hand::Scissors => RoundResult::Win,
-            },
-            Hand::Scissors => match you {
-                Hand::Rock => RoundResult::Win,
-                Hand::Paper => RoundResult::Loss,
-                Hand::Scissors => RoundResult::Draw,
-            },
-        };
-        Round { you, result }
-    }
-
-    fn from_modified_strategy(opponent: Hand, result: RoundResult) -> Self {
-        let you = match opponent {
-            Hand::Rock => match result {
-                RoundResult::Draw => Hand::Rock,
-                RoundResult::Win => Hand::Paper,
-                RoundResult::Loss => Hand::Scissors,
-            },
-            Hand::Paper => match result {
-                RoundResult::Loss => Hand::Rock,
-                RoundResult::Draw => Hand::Paper,
-                RoundResult::Win => Hand::Scissors,
-            },
-            Hand::Scissors => match result {
-                RoundResult::Win => Hand::Rock,
-                RoundResult::Loss => Hand::Paper,
-                RoundResult::Draw => Hand::Scissors,
-            },
-        };
-        Round { you, result }
+    fn score(&self, game: &Game) -> u32 {
+        Round::score_for(game, self.you, self.result)
     }
 
-    fn score(&self) -> u8 {
-        let selected = match self.you {
-            Hand::Rock => 1,
-            Hand::Paper => 2,
-            Hand::Scissors => 3,
-        };
-        let outcome = match self.result {
-            RoundResult::Win => 6,
-            RoundResult::Loss => 0,
-            RoundResult::Draw => 3,
+    fn opponent_score(&self, game: &Game) -> u32 {
+        Round::score_for(game, self.opponent, self.result.reverse())
+    }
+
+    fn score_for(game: &Game, hand: Hand, result: RoundResult) -> u32 {
+        let selected = game.hand_scores[hand.0];
+        let outcome = match result {
+            RoundResult::Loss => game.outcome_scores[0],
+            RoundResult::Draw => game.outcome_scores[1],
+            RoundResult::Win => game.outcome_scores[2],
         };
         selected + outcome
     }
 }
 
-fn load(path: &Path, strategy: Strategy) -> Vec<Round> {
+/// Reads the strategy guide as raw `(opponent, ours)` symbol pairs.
+fn load_guide(path: &Path) -> Vec<(String, String)> {
     let input = fs::read_to_string(path).expect("file not found");
-    let mut rounds: Vec<Round> = vec![];
+    let mut guide = vec![];
     for line in input.lines() {
         let parts: Vec<&str> = line.split(' ').map(|p| p.trim()).collect();
+        guide.push((parts[0].to_string(), parts[1].to_string()));
+    }
+    guide
+}
+
+fn decode(game: &Game, guide: &[(String, String)], strategy: Strategy) -> Vec<Round> {
+    let mut rounds: Vec<Round> = vec![];
+    for (opponent, ours) in guide {
         let round = match strategy {
             Strategy::Original => Round::from_original_strategy(
-                Hand::from_encrypted_strategy(parts[1]),
-                Hand::from_unencrypted(parts[0]),
+                game,
+                game.hand_from_encrypted_strategy(ours),
+                game.hand_from_unencrypted(opponent),
             ),
             Strategy::New => Round::from_modified_strategy(
-                Hand::from_unencrypted(parts[0]),
-                RoundResult::from_string(parts[1]),
+                game,
+                game.hand_from_unencrypted(opponent),
+                game.result_from_string(ours),
             ),
         };
         rounds.push(round);
@@ -137,22 +259,32 @@ fn load(path: &Path, strategy: Strategy) -> Vec<Round> {
     rounds
 }
 
+fn load(path: &Path, game: &Game, strategy: Strategy) -> Vec<Round> {
+    decode(game, &load_guide(path), strategy)
+}
+
 #[allow(dead_code)]
 pub fn star_1() -> u32 {
-    let rounds = load(Path::new("src/dec02/testdata.txt"), Strategy::Original);
+    let game = Game::rock_paper_scissors();
+    let rounds = load(
+        Path::new("src/dec02/testdata.txt"),
+        &game,
+        Strategy::Original,
+    );
     let mut total_score = 0;
     for round in rounds {
-        total_score += round.score() as u32;
+        total_score += round.score(&game);
     }
     total_score
 }
 
 #[allow(dead_code)]
 pub fn star_2() -> u32 {
-    let rounds = load(Path::new("src/dec02/testdata.txt"), Strategy::New);
+    let game = Game::rock_paper_scissors();
+    let rounds = load(Path::new("src/dec02/testdata.txt"), &game, Strategy::New);
     let mut total_score = 0;
     for round in rounds {
-        total_score += round.score() as u32;
+        total_score += round.score(&game);
     }
     total_score
 }
@@ -170,4 +302,64 @@ mod test {
     fn test_star_2() {
         assert_eq!(star_2(), 11998);
     }
+
+    #[test]
+    fn test_cyclic_dominance() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let hand = |name: &str| Hand(game.hands.iter().position(|h| h == name).unwrap());
+        assert_eq!(game.play(hand("Spock"), hand("Scissors")), RoundResult::Win);
+        assert_eq!(game.play(hand("Lizard"), hand("Spock")), RoundResult::Win);
+        assert_eq!(game.play(hand("Rock"), hand("Lizard")), RoundResult::Win);
+        assert_eq!(
+            game.play(hand("Paper"), hand("Scissors")),
+            RoundResult::Loss
+        );
+        assert_eq!(game.play(hand("Paper"), hand("Paper")), RoundResult::Draw);
+
+        let game = Game::rock_paper_scissors_7();
+        for you in 0..game.num_hands() {
+            let wins = (0..game.num_hands())
+                .filter(|&opponent| game.play(Hand(you), Hand(opponent)) == RoundResult::Win)
+                .count();
+            assert_eq!(wins, 3);
+            for result in [RoundResult::Win, RoundResult::Draw, RoundResult::Loss] {
+                assert_eq!(
+                    game.play(game.respond(Hand(you), result), Hand(you)),
+                    result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_game_builder_validation() {
+        let game = GameBuilder::default()
+            .hands(vec!["Rock".to_string(), "Paper".to_string()])
+            .opponent_symbols(vec!["A".to_string(), "B".to_string()])
+            .your_symbols(vec!["X".to_string(), "Y".to_string()])
+            .result_symbols(["X".to_string(), "Y".to_string(), "Z".to_string()])
+            .hand_scores(vec![1, 2])
+            .outcome_scores([0, 3, 6])
+            .build();
+        assert!(game.is_err());
+
+        let game = GameBuilder::default()
+            .hands(vec![
+                "Rock".to_string(),
+                "Paper".to_string(),
+                "Scissors".to_string(),
+            ])
+            .opponent_symbols(vec!["A".to_string(), "B".to_string(), "A".to_string()])
+            .your_symbols(vec!["X".to_string(), "Y".to_string(), "Z".to_string()])
+            .result_symbols(["X".to_string(), "Y".to_string(), "Z".to_string()])
+            .hand_scores(vec![1, 2, 3])
+            .outcome_scores([0, 3, 6])
+            .build();
+        assert!(game.is_err());
+
+        let hands: Vec<String> = (0..27).map(|idx| idx.to_string()).collect();
+        let hands: Vec<&str> = hands.iter().map(String::as_str).collect();
+        assert!(Game::cyclic(&hands).is_err());
+        assert!(Game::cyclic(&hands[..25]).is_ok());
+    }
 }
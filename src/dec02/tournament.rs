@@ -0,0 +1,245 @@
+use std::{cmp::Reverse, fmt::Write, path::Path};
+
+use super::{load, Game, Hand, Round, RoundResult, Strategy};
+
+/// The outcome of a single round, with both players' running totals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RoundReport {
+    you: Hand,
+    opponent: Hand,
+    result: RoundResult,
+    your_score: u32,
+    opponent_score: u32,
+    your_total: u32,
+    opponent_total: u32,
+}
+
+/// A run of consecutive rounds with the same result, from our point of view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Streak {
+    result: RoundResult,
+    /// Index of the first round in the streak.
+    start: usize,
+    length: usize,
+}
+
+/// A player's row in the final standings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Standing {
+    player: &'static str,
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    score: u32,
+}
+
+enum TableFormat {
+    Text,
+    Csv,
+}
+
+#[derive(Debug)]
+struct Tournament {
+    rounds: Vec<RoundReport>,
+    streaks: Vec<Streak>,
+}
+
+impl Tournament {
+    fn simulate(game: &Game, rounds: &[Round]) -> Self {
+        let mut reports: Vec<RoundReport> = Vec::new();
+        let mut streaks: Vec<Streak> = Vec::new();
+        let (mut your_total, mut opponent_total) = (0, 0);
+        for (idx, round) in rounds.iter().enumerate() {
+            let your_score = round.score(game);
+            let opponent_score = round.opponent_score(game);
+            your_total += your_score;
+            opponent_total += opponent_score;
+            reports.push(RoundReport {
+                you: round.you,
+                opponent: round.opponent,
+                result: round.result,
+                your_score,
+                opponent_score,
+                your_total,
+                opponent_total,
+            });
+            match streaks.last_mut() {
+                Some(streak) if streak.result == round.result => streak.length += 1,
+                _ => streaks.push(Streak {
+                    result: round.result,
+                    start: idx,
+                    length: 1,
+                }),
+            }
+        }
+        Tournament {
+            rounds: reports,
+            streaks,
+        }
+    }
+
+    /// The longest streak of [result], preferring the earliest on ties.
+    fn longest_streak(&self, result: RoundResult) -> Option<&Streak> {
+        self.streaks
+            .iter()
+            .filter(|streak| streak.result == result)
+            .fold(None, |longest: Option<&Streak>, streak| match longest {
+                Some(longest) if longest.length >= streak.length => Some(longest),
+                _ => Some(streak),
+            })
+    }
+
+    /// The final standings, highest score first.
+    fn standings(&self) -> Vec<Standing> {
+        let count = |result: RoundResult| {
+            self.rounds
+                .iter()
+                .filter(|round| round.result == result)
+                .count()
+        };
+        let (wins, draws, losses) = (
+            count(RoundResult::Win),
+            count(RoundResult::Draw),
+            count(RoundResult::Loss),
+        );
+        let last = self.rounds.last();
+        let mut standings = vec![
+            Standing {
+                player: "You",
+                wins,
+                draws,
+                losses,
+                score: last.map_or(0, |round| round.your_total),
+            },
+            Standing {
+                player: "Opponent",
+                wins: losses,
+                draws,
+                losses: wins,
+                score: last.map_or(0, |round| round.opponent_total),
+            },
+        ];
+        standings.sort_by_key(|standing| Reverse(standing.score));
+        standings
+    }
+
+    fn standings_table(&self, format: TableFormat) -> String {
+        let mut table = String::new();
+        match format {
+            TableFormat::Text => {
+                writeln!(
+                    table,
+                    "{:<10} {:>6} {:>6} {:>6} {:>8}",
+                    "Player", "Wins", "Draws", "Losses", "Score"
+                )
+                .unwrap();
+                for s in self.standings() {
+                    writeln!(
+                        table,
+                        "{:<10} {:>6} {:>6} {:>6} {:>8}",
+                        s.player, s.wins, s.draws, s.losses, s.score
+                    )
+                    .unwrap();
+                }
+            }
+            TableFormat::Csv => {
+                writeln!(table, "player,wins,draws,losses,score").unwrap();
+                for s in self.standings() {
+                    writeln!(
+                        table,
+                        "{},{},{},{},{}",
+                        s.player, s.wins, s.draws, s.losses, s.score
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        table
+    }
+}
+
+/// Prints a round-by-round account of the guide at [path] played with
+/// [Strategy::Original], followed by the streaks and the final standings as
+/// text or, if [csv] is set, as CSV.
+#[allow(dead_code)]
+pub fn report(path: &Path, csv: bool) {
+    let game = Game::rock_paper_scissors();
+    let tournament = Tournament::simulate(&game, &load(path, &game, Strategy::Original));
+    for (idx, round) in tournament.rounds.iter().enumerate() {
+        println!(
+            "Round {}: {} vs {} -> {:?} ({} - {}), running {} - {}",
+            idx + 1,
+            game.hands[round.you.0],
+            game.hands[round.opponent.0],
+            round.result,
+            round.your_score,
+            round.opponent_score,
+            round.your_total,
+            round.opponent_total
+        );
+    }
+    for result in [RoundResult::Win, RoundResult::Draw, RoundResult::Loss] {
+        if let Some(streak) = tournament.longest_streak(result) {
+            println!(
+                "Longest {:?} streak: {} from round {}",
+                result,
+                streak.length,
+                streak.start + 1
+            );
+        }
+    }
+    let format = if csv {
+        TableFormat::Csv
+    } else {
+        TableFormat::Text
+    };
+    print!("{}", tournament.standings_table(format));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let game = Game::rock_paper_scissors();
+        let rounds = load(
+            Path::new("src/dec02/example.txt"),
+            &game,
+            Strategy::Original,
+        );
+        let tournament = Tournament::simulate(&game, &rounds);
+        let totals: Vec<(u32, u32)> = tournament
+            .rounds
+            .iter()
+            .map(|round| (round.your_total, round.opponent_total))
+            .collect();
+        assert_eq!(totals, vec![(8, 1), (9, 9), (15, 15)]);
+        assert_eq!(
+            tournament.longest_streak(RoundResult::Win),
+            Some(&Streak {
+                result: RoundResult::Win,
+                start: 0,
+                length: 1
+            })
+        );
+        assert_eq!(
+            tournament.standings_table(TableFormat::Csv),
+            "player,wins,draws,losses,score\nYou,1,1,1,15\nOpponent,1,1,1,15\n"
+        );
+    }
+
+    #[test]
+    fn test_totals_match_stars() {
+        let game = Game::rock_paper_scissors();
+        for (strategy, expected) in [(Strategy::Original, 8933), (Strategy::New, 11998)] {
+            let rounds = load(Path::new("src/dec02/testdata.txt"), &game, strategy);
+            let tournament = Tournament::simulate(&game, &rounds);
+            let you = tournament.standings();
+            let you = you.iter().find(|s| s.player == "You").unwrap();
+            assert_eq!(you.score, expected);
+            let streak_rounds: usize = tournament.streaks.iter().map(|s| s.length).sum();
+            assert_eq!(streak_rounds, rounds.len());
+        }
+    }
+}
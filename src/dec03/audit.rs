@@ -0,0 +1,165 @@
+use std::path::Path;
+
+use super::{load, Backpack, Item, Items};
+
+/// Priority sums under different readings of rucksacks or groups that don't
+/// share exactly one item.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Interpretations {
+    /// Counts only rucksacks or groups that share exactly one item.
+    unique_only: u32,
+    /// Counts every shared item.
+    all_shared: u32,
+    /// Counts the lowest-priority shared item.
+    lowest: u32,
+    /// Counts the highest-priority shared item.
+    highest: u32,
+}
+
+impl Interpretations {
+    fn add(&mut self, shared: Items) {
+        let priorities: Vec<u32> = shared
+            .iter()
+            .map(|item| item.get_priority() as u32)
+            .collect();
+        if priorities.len() == 1 {
+            self.unique_only += priorities[0];
+        }
+        self.all_shared += priorities.iter().sum::<u32>();
+        self.lowest += priorities.first().unwrap_or(&0);
+        self.highest += priorities.last().unwrap_or(&0);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Audit {
+    /// Rucksacks whose compartments share no item or several, by line index.
+    misplaced: Vec<(usize, Vec<Item>)>,
+    /// Groups that share no item or several, by group index.
+    ambiguous_badges: Vec<(usize, Vec<Item>)>,
+    /// Rucksacks left over after the last full group.
+    ungrouped: usize,
+    misplaced_totals: Interpretations,
+    badge_totals: Interpretations,
+}
+
+fn audit(backpacks: &[Backpack], group_size: usize) -> Audit {
+    let mut misplaced = Vec::new();
+    let mut misplaced_totals = Interpretations::default();
+    for (idx, backpack) in backpacks.iter().enumerate() {
+        let shared = backpack.0 & backpack.1;
+        if shared.len() != 1 {
+            misplaced.push((idx, shared.iter().collect()));
+        }
+        misplaced_totals.add(shared);
+    }
+
+    let mut ambiguous_badges = Vec::new();
+    let mut badge_totals = Interpretations::default();
+    // Groups of no rucksacks leave every rucksack over.
+    let mut ungrouped = backpacks.len();
+    if group_size > 0 {
+        let groups = backpacks.chunks_exact(group_size);
+        ungrouped = groups.remainder().len();
+        for (idx, group) in groups.enumerate() {
+            let shared = group
+                .iter()
+                .fold(Items::ALL, |accum, backpack| accum & backpack.items());
+            if shared.len() != 1 {
+                ambiguous_badges.push((idx, shared.iter().collect()));
+            }
+            badge_totals.add(shared);
+        }
+    }
+
+    Audit {
+        misplaced,
+        ambiguous_badges,
+        ungrouped,
+        misplaced_totals,
+        badge_totals,
+    }
+}
+
+/// Prints an audit of the rucksacks at [path], grouped by [group_size].
+#[allow(dead_code)]
+pub fn report(path: &Path, group_size: usize) {
+    let audit = audit(&load(path), group_size);
+    for (line, items) in &audit.misplaced {
+        println!(
+            "Rucksack {}: {} misplaced {:?}",
+            line + 1,
+            items.len(),
+            items
+        );
+    }
+    for (group, items) in &audit.ambiguous_badges {
+        println!(
+            "Group {}: {} candidate badges {:?}",
+            group + 1,
+            items.len(),
+            items
+        );
+    }
+    if audit.ungrouped > 0 {
+        println!("{} rucksacks left over", audit.ungrouped);
+    }
+    println!("Misplaced: {:?}", audit.misplaced_totals);
+    println!("Badges: {:?}", audit.badge_totals);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clean_input() {
+        let audit = audit(&load(Path::new("src/dec03/testdata.txt")), 3);
+        assert!(audit.misplaced.is_empty());
+        assert!(audit.ambiguous_badges.is_empty());
+        assert_eq!(audit.ungrouped, 0);
+        assert_eq!(audit.misplaced_totals.unique_only, 7903);
+        assert_eq!(audit.misplaced_totals.all_shared, 7903);
+        assert_eq!(audit.badge_totals.unique_only, 2548);
+    }
+
+    #[test]
+    fn test_faulty_input() {
+        let backpacks = vec![
+            Backpack(Items::from("ab"), Items::from("cd")),
+            Backpack(Items::from("ab"), Items::from("ab")),
+            Backpack(Items::from("aZ"), Items::from("Zx")),
+            Backpack(Items::from("xy"), Items::from("zx")),
+        ];
+        let triples = audit(&backpacks, 3);
+        assert_eq!(
+            triples.misplaced,
+            vec![(0, vec![]), (1, vec![Item('a'), Item('b')])]
+        );
+        assert_eq!(triples.ambiguous_badges, vec![]);
+        assert_eq!(triples.ungrouped, 1);
+        assert_eq!(
+            triples.misplaced_totals,
+            Interpretations {
+                unique_only: 52 + 24,
+                all_shared: 1 + 2 + 52 + 24,
+                lowest: 1 + 52 + 24,
+                highest: 2 + 52 + 24,
+            }
+        );
+        assert_eq!(triples.badge_totals.unique_only, 1);
+
+        let pairs = audit(&backpacks[..2], 2);
+        assert_eq!(
+            pairs.ambiguous_badges,
+            vec![(0, vec![Item('a'), Item('b')])]
+        );
+        assert_eq!(pairs.badge_totals.unique_only, 0);
+        assert_eq!(pairs.badge_totals.all_shared, 3);
+
+        let empty = audit(&backpacks, 0);
+        assert_eq!(empty.ambiguous_badges, vec![]);
+        assert_eq!(empty.ungrouped, 4);
+        assert_eq!(empty.badge_totals, Interpretations::default());
+    }
+}
@@ -0,0 +1,117 @@
+//! Compares the bitmask rucksacks against the original `HashSet` ones.
+//!
+//! Run with `cargo test --release dec03::bench -- --ignored --nocapture`.
+
+use std::{collections::HashSet, time::Instant};
+
+use super::{parse, sum_badges, sum_shared_items, Item};
+use crate::random::Random;
+
+/// The original representation, one `HashSet` per compartment.
+struct HashBackpack(HashSet<Item>, HashSet<Item>);
+
+fn hash_parse(input: &str) -> Vec<HashBackpack> {
+    let mut backpacks = Vec::new();
+    for line in input.lines() {
+        let (left, right) = (&line[0..line.len() / 2], &line[line.len() / 2..]);
+        backpacks.push(HashBackpack(
+            left.chars().map(Item).collect(),
+            right.chars().map(Item).collect(),
+        ));
+    }
+    backpacks
+}
+
+fn hash_sum_shared_items(backpacks: &[HashBackpack]) -> u32 {
+    backpacks
+        .iter()
+        .map(|backpack| {
+            let intersection: Vec<&Item> = backpack.0.intersection(&backpack.1).collect();
+            assert_eq!(intersection.len(), 1);
+            intersection[0].get_priority() as u32
+        })
+        .sum()
+}
+
+fn hash_sum_badges(backpacks: &[HashBackpack]) -> u32 {
+    backpacks
+        .chunks_exact(3)
+        .map(|group| {
+            let mut all_items = HashSet::new();
+            all_items.extend(('a'..='z').map(Item));
+            all_items.extend(('A'..='Z').map(Item));
+            for elf in group {
+                all_items = all_items
+                    .intersection(&elf.0.union(&elf.1).copied().collect())
+                    .copied()
+                    .collect();
+            }
+            assert_eq!(all_items.len(), 1);
+            all_items.iter().next().unwrap().get_priority() as u32
+        })
+        .sum()
+}
+
+/// Generates [groups] groups of three valid rucksacks.
+fn generate(groups: usize) -> String {
+    let mut random = Random::new(0x2022_1203);
+    let mut next = |bound| random.below(bound);
+    let mut input = String::new();
+    for _ in 0..groups {
+        let mut items: Vec<char> = (1..=52).map(|p| Item::from_priority(p).0).collect();
+        let badge = items.swap_remove(next(items.len()));
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, next(idx + 1));
+        }
+        // Each elf gets 17 items no other elf in the group carries.
+        for own in items.chunks_exact(17) {
+            let shared = own[0];
+            let left: String = [badge, shared].iter().chain(&own[1..9]).collect();
+            let right: String = [shared, own[9]].iter().chain(&own[9..17]).collect();
+            input.push_str(&left);
+            input.push_str(&right);
+            input.push('\n');
+        }
+    }
+    input
+}
+
+#[test]
+fn test_matches_hash_sets() {
+    let input = generate(1_000);
+    let backpacks = parse(&input);
+    let hash_backpacks = hash_parse(&input);
+    assert_eq!(
+        sum_shared_items(&backpacks),
+        hash_sum_shared_items(&hash_backpacks)
+    );
+    assert_eq!(sum_badges(&backpacks, 3), hash_sum_badges(&hash_backpacks));
+}
+
+#[test]
+#[ignore]
+fn bench_large_input() {
+    let input = generate(1_000_000);
+
+    let start = Instant::now();
+    let backpacks = parse(&input);
+    let bits = (sum_shared_items(&backpacks), sum_badges(&backpacks, 3));
+    let bits_elapsed = start.elapsed();
+
+    let start = Instant::now();
+    let hash_backpacks = hash_parse(&input);
+    let hashes = (
+        hash_sum_shared_items(&hash_backpacks),
+        hash_sum_badges(&hash_backpacks),
+    );
+    let hash_elapsed = start.elapsed();
+
+    assert_eq!(bits, hashes);
+    println!(
+        "{} rucksacks: bitmask {:?}, HashSet {:?} ({:.1}x)",
+        backpacks.len(),
+        bits_elapsed,
+        hash_elapsed,
+        hash_elapsed.as_secs_f64() / bits_elapsed.as_secs_f64()
+    );
+}
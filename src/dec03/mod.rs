@@ -1,4 +1,13 @@
-use std::{collections::HashSet, fs, path::Path};
+use std::{
+    fs,
+    ops::{BitAnd, BitOr},
+    path::Path,
+};
+
+mod audit;
+#[cfg(test)]
+mod bench;
+mod plan;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 struct Item(char);
@@ -13,43 +22,112 @@ impl Item {
             }
         }
     }
+
+    fn from_priority(priority: u8) -> Self {
+        match priority {
+            1..=26 => Item((b'a' + priority - 1) as char),
+            27..=52 => Item((b'A' + priority - 27) as char),
+            _ => {
+                panic!("unexpected priority: {}", priority);
+            }
+        }
+    }
+}
+
+/// A set of item types, with bit `p - 1` set for an item of priority `p`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct Items(u64);
+
+impl Items {
+    const ALL: Items = Items((1 << 52) - 1);
+
+    fn insert(&mut self, item: Item) {
+        self.0 |= 1 << (item.get_priority() - 1);
+    }
+
+    fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    fn iter(&self) -> ItemsIter {
+        ItemsIter(self.0)
+    }
 }
 
-struct Backpack(HashSet<Item>, HashSet<Item>);
+impl From<&str> for Items {
+    fn from(s: &str) -> Self {
+        s.chars().fold(Items::default(), |mut accum, item| {
+            accum.insert(Item(item));
+            accum
+        })
+    }
+}
+
+impl BitAnd for Items {
+    type Output = Items;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Items(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Items {
+    type Output = Items;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Items(self.0 | rhs.0)
+    }
+}
+
+/// Yields the items in a set from lowest to highest priority.
+struct ItemsIter(u64);
+
+impl Iterator for ItemsIter {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros();
+        self.0 &= self.0 - 1;
+        Some(Item::from_priority(bit as u8 + 1))
+    }
+}
+
+struct Backpack(Items, Items);
 
 impl Backpack {
-    fn shared_item(&self) -> &Item {
-        let intersection: Vec<&Item> = self.0.intersection(&self.1).collect();
+    fn items(&self) -> Items {
+        self.0 | self.1
+    }
+
+    fn shared_item(&self) -> Item {
+        let intersection = self.0 & self.1;
         assert_eq!(
             intersection.len(),
             1,
             "no items shared between compartments"
         );
-        intersection.last().unwrap()
+        intersection.iter().next().unwrap()
     }
 }
 
-fn load(path: &Path) -> Vec<Backpack> {
-    let input = fs::read_to_string(path).expect("file not found");
+fn parse(input: &str) -> Vec<Backpack> {
     let mut backpacks = Vec::new();
     for line in input.lines() {
         let (left, right) = (&line[0..line.len() / 2], &line[line.len() / 2..]);
-        let left_cpt = left.chars().fold(HashSet::new(), |mut accum, item| {
-            accum.insert(Item(item));
-            accum
-        });
-        let right_cpt = right.chars().fold(HashSet::new(), |mut accum, item| {
-            accum.insert(Item(item));
-            accum
-        });
-        backpacks.push(Backpack(left_cpt, right_cpt));
+        backpacks.push(Backpack(Items::from(left), Items::from(right)));
     }
     backpacks
 }
 
-#[allow(dead_code)]
-pub fn star_1() -> u32 {
-    let backpacks = load(Path::new("src/dec03/testdata.txt"));
+fn load(path: &Path) -> Vec<Backpack> {
+    let input = fs::read_to_string(path).expect("file not found");
+    parse(&input)
+}
+
+fn sum_shared_items(backpacks: &[Backpack]) -> u32 {
     let mut sum: u32 = 0;
     for backpack in backpacks {
         let shared_item = backpack.shared_item();
@@ -58,36 +136,142 @@ pub fn star_1() -> u32 {
     sum
 }
 
-#[allow(dead_code)]
-pub fn star_2() -> u32 {
-    let backpacks = load(Path::new("src/dec03/testdata.txt"));
-    backpacks.chunks_exact(3).fold(0, |mut accum, group| {
-        if let [elf_1, elf_2, elf_3] = group {
-            let mut all_items = HashSet::new();
-            all_items.extend(('a'..='z').map(Item));
-            all_items.extend(('A'..='Z').map(Item));
-            all_items = all_items
-                .intersection(&elf_1.0.union(&elf_1.1).copied().collect()).copied()
-                .collect();
-            all_items = all_items
-                .intersection(&elf_2.0.union(&elf_2.1).copied().collect()).copied()
-                .collect();
-            all_items = all_items
-                .intersection(&elf_3.0.union(&elf_3.1).copied().collect()).copied()
-                .collect();
-            assert_eq!(
-                all_items.len(),
-                1,
-                "more than one shared item: {:?}",
-                all_items
-            );
-            let shared_item: &Item = all_items.iter().last().unwrap();
+/// The item shared by every rucksack in [group], if there is exactly one.
+fn badge(group: &[&Backpack]) -> Option<Item> {
+    let all_items = group
+        .iter()
+        .fold(Items::ALL, |accum, backpack| accum & backpack.items());
+    match all_items.len() {
+        1 => all_items.iter().next(),
+        _ => None,
+    }
+}
+
+/// The sum of the badge priorities of each group of [group_size] rucksacks.
+/// Rucksacks left over after the last full group aren't counted, and nor is
+/// any rucksack when [group_size] is 0.
+fn sum_badges(backpacks: &[Backpack], group_size: usize) -> u32 {
+    if group_size == 0 {
+        return 0;
+    }
+    backpacks
+        .chunks_exact(group_size)
+        .fold(0, |mut accum, group| {
+            let group: Vec<&Backpack> = group.iter().collect();
+            let shared_item = badge(&group).unwrap_or_else(|| {
+                panic!(
+                    "expected exactly one shared item: {:?}",
+                    group
+                        .iter()
+                        .fold(Items::ALL, |accum, backpack| accum & backpack.items())
+                        .iter()
+                        .collect::<Vec<Item>>()
+                )
+            });
             accum += shared_item.get_priority() as u32;
+            accum
+        })
+}
+
+/// Searches for a way to split [backpacks], in any order, into groups of
+/// [group_size] that each share exactly one badge. Returns the indices of
+/// each group's rucksacks along with its badge, or `None` if the exhaustive
+/// search finds no such partition.
+fn find_groups(backpacks: &[Backpack], group_size: usize) -> Option<Vec<(Vec<usize>, Item)>> {
+    fn search(
+        backpacks: &[Backpack],
+        group_size: usize,
+        assigned: &mut Vec<bool>,
+        group: &mut Vec<usize>,
+        shared: Items,
+        groups: &mut Vec<(Vec<usize>, Item)>,
+    ) -> bool {
+        if group.len() == group_size {
+            if shared.len() != 1 {
+                return false;
+            }
+            groups.push((group.clone(), shared.iter().next().unwrap()));
+            let mut done = Vec::new();
+            std::mem::swap(group, &mut done);
+            if search(backpacks, group_size, assigned, group, Items::ALL, groups) {
+                return true;
+            }
+            *group = done;
+            groups.pop();
+            return false;
+        }
+        // Each group is anchored on the first unassigned rucksack, and the
+        // rest are picked in increasing order, so no partition is tried twice.
+        let start = match group.last() {
+            Some(&last) => last + 1,
+            None => match assigned.iter().position(|&a| !a) {
+                Some(first) => first,
+                None => return true,
+            },
+        };
+        let candidates = if group.is_empty() {
+            start..start + 1
         } else {
-            panic!("unexpected group: {}", group.len());
+            start..backpacks.len()
+        };
+        for idx in candidates {
+            if assigned[idx] {
+                continue;
+            }
+            let next_shared = shared & backpacks[idx].items();
+            if next_shared.len() == 0 {
+                continue;
+            }
+            assigned[idx] = true;
+            group.push(idx);
+            if search(backpacks, group_size, assigned, group, next_shared, groups) {
+                return true;
+            }
+            group.pop();
+            assigned[idx] = false;
         }
-        accum
-    })
+        false
+    }
+
+    if !backpacks.len().is_multiple_of(group_size) {
+        return None;
+    }
+    let mut groups = Vec::new();
+    let found = search(
+        backpacks,
+        group_size,
+        &mut vec![false; backpacks.len()],
+        &mut Vec::new(),
+        Items::ALL,
+        &mut groups,
+    );
+    found.then_some(groups)
+}
+
+#[allow(dead_code)]
+pub fn star_1() -> u32 {
+    let backpacks = load(Path::new("src/dec03/testdata.txt"));
+    sum_shared_items(&backpacks)
+}
+
+#[allow(dead_code)]
+pub fn star_2() -> u32 {
+    let backpacks = load(Path::new("src/dec03/testdata.txt"));
+    sum_badges(&backpacks, 3)
+}
+
+/// Sums the badges of the testdata's rucksacks as if their order had been
+/// lost, or returns `None` if they can't be grouped.
+#[allow(dead_code)]
+pub fn star_2_unordered(group_size: usize) -> Option<u32> {
+    let backpacks = load(Path::new("src/dec03/testdata.txt"));
+    let groups = find_groups(&backpacks, group_size)?;
+    Some(
+        groups
+            .iter()
+            .map(|(_, badge)| badge.get_priority() as u32)
+            .sum(),
+    )
 }
 
 #[cfg(test)]
@@ -103,4 +287,65 @@ mod test {
     fn test_star_2() {
         assert_eq!(star_2(), 2548);
     }
+
+    #[test]
+    fn test_items() {
+        let items = Items::from("azAZ");
+        assert_eq!(items.len(), 4);
+        assert_eq!(
+            items.iter().collect::<Vec<Item>>(),
+            vec![Item('a'), Item('z'), Item('A'), Item('Z')]
+        );
+        assert_eq!(Items::ALL.iter().count(), 52);
+        assert_eq!(
+            (items & Items::from("bZ")).iter().collect::<Vec<Item>>(),
+            vec![Item('Z')]
+        );
+    }
+
+    #[test]
+    fn test_group_size() {
+        let backpacks = load(Path::new("src/dec03/example.txt"));
+        assert_eq!(sum_badges(&backpacks, 3), 70);
+        let pairs = vec![
+            Backpack(Items::from("ab"), Items::from("cd")),
+            Backpack(Items::from("ax"), Items::from("yz")),
+            Backpack(Items::from("BC"), Items::from("DE")),
+            Backpack(Items::from("Bx"), Items::from("yz")),
+        ];
+        assert_eq!(sum_badges(&pairs, 2), 1 + 28);
+    }
+
+    #[test]
+    fn test_ungrouped() {
+        let backpacks = load(Path::new("src/dec03/example.txt"));
+        assert_eq!(sum_badges(&backpacks[..5], 3), 18);
+        assert_eq!(sum_badges(&backpacks, 0), 0);
+        assert_eq!(sum_badges(&[], 0), 0);
+    }
+
+    #[test]
+    fn test_find_groups() {
+        let backpacks = load(Path::new("src/dec03/example.txt"));
+        let shuffled: Vec<Backpack> = [4, 0, 5, 2, 3, 1]
+            .iter()
+            .map(|&idx| Backpack(backpacks[idx].0, backpacks[idx].1))
+            .collect();
+        let groups = find_groups(&shuffled, 3).unwrap();
+        let mut seen: Vec<usize> = groups.iter().flat_map(|(group, _)| group.clone()).collect();
+        seen.sort();
+        assert_eq!(seen, (0..6).collect::<Vec<usize>>());
+        for (group, item) in groups {
+            let group: Vec<&Backpack> = group.iter().map(|&idx| &shuffled[idx]).collect();
+            assert_eq!(badge(&group), Some(item));
+        }
+
+        let strangers = vec![
+            Backpack(Items::from("a"), Items::from("a")),
+            Backpack(Items::from("b"), Items::from("b")),
+            Backpack(Items::from("a"), Items::from("b")),
+        ];
+        assert_eq!(find_groups(&strangers, 3), None);
+        assert_eq!(find_groups(&strangers, 2), None);
+    }
 }
@@ -0,0 +1,187 @@
+use std::{fs, path::Path};
+
+use super::Item;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// Moves [count] copies of [item] into the compartment on side [to].
+#[derive(Debug, PartialEq, Eq)]
+struct Transfer {
+    item: Item,
+    count: u32,
+    to: Side,
+}
+
+/// The fewest item moves that make a rucksack's compartments disjoint while
+/// keeping them the same size.
+#[derive(Debug, PartialEq, Eq)]
+struct Plan(Vec<Transfer>);
+
+impl Plan {
+    fn cost(&self) -> u32 {
+        self.0.iter().map(|transfer| transfer.count).sum()
+    }
+}
+
+fn count(compartment: &str) -> [u32; 52] {
+    let mut counts = [0; 52];
+    for item in compartment.chars() {
+        counts[Item(item).get_priority() as usize - 1] += 1;
+    }
+    counts
+}
+
+/// Plans the rearrangement of a rucksack with compartments [left] and
+/// [right], or returns `None` if no arrangement can keep them the same size.
+///
+/// Every item type ends up entirely on one side. Keeping a type on the left
+/// costs moving its right-hand copies over and vice versa, and the sizes stay
+/// equal exactly when as many items move left as move right, so we search
+/// over the difference between the two.
+fn plan(left: &str, right: &str) -> Option<Plan> {
+    let (left_counts, right_counts) = (count(left), count(right));
+    let types: Vec<usize> = (0..52)
+        .filter(|&t| left_counts[t] > 0 || right_counts[t] > 0)
+        .collect();
+    // Differences between items moved left and items moved right range over
+    // -left.len()..=right.len(), shifted by `offset` to index from zero.
+    let offset = left.len() as i64;
+    let width = left.len() + right.len() + 1;
+
+    // best[k][d] is the cheapest way, and the side chosen for the k-th type,
+    // to place the first k + 1 types with difference d.
+    let mut best: Vec<Vec<Option<(u32, Side)>>> = Vec::with_capacity(types.len());
+    let mut costs: Vec<Option<u32>> = vec![None; width];
+    costs[offset as usize] = Some(0);
+    for &t in &types {
+        let mut next: Vec<Option<(u32, Side)>> = vec![None; width];
+        for (diff, cost) in costs.iter().enumerate() {
+            let Some(cost) = cost else { continue };
+            for (side, shift, moved) in [
+                (Side::Left, right_counts[t] as i64, right_counts[t]),
+                (Side::Right, -(left_counts[t] as i64), left_counts[t]),
+            ] {
+                let target = diff as i64 + shift;
+                if target < 0 || target >= width as i64 {
+                    continue;
+                }
+                let slot = &mut next[target as usize];
+                if slot.is_none_or(|(best_cost, _)| cost + moved < best_cost) {
+                    *slot = Some((cost + moved, side));
+                }
+            }
+        }
+        costs = next.iter().map(|slot| slot.map(|(cost, _)| cost)).collect();
+        best.push(next);
+    }
+    costs[offset as usize]?;
+
+    let mut transfers = Vec::new();
+    let mut diff = offset;
+    for (k, &t) in types.iter().enumerate().rev() {
+        let (_, side) = best[k][diff as usize].unwrap();
+        let (moved, shift) = match side {
+            Side::Left => (right_counts[t], right_counts[t] as i64),
+            Side::Right => (left_counts[t], -(left_counts[t] as i64)),
+        };
+        if moved > 0 {
+            transfers.push(Transfer {
+                item: Item::from_priority(t as u8 + 1),
+                count: moved,
+                to: side,
+            });
+        }
+        diff -= shift;
+    }
+    transfers.reverse();
+    Some(Plan(transfers))
+}
+
+#[derive(Debug)]
+struct Reorganisation {
+    /// Each rucksack's plan, or `None` if it can't be fixed.
+    plans: Vec<Option<Plan>>,
+    /// The number of item moves across every rucksack that can be fixed.
+    total_cost: u32,
+}
+
+fn reorganise(input: &str) -> Reorganisation {
+    let plans: Vec<Option<Plan>> = input
+        .lines()
+        .map(|line| plan(&line[0..line.len() / 2], &line[line.len() / 2..]))
+        .collect();
+    let total_cost = plans.iter().flatten().map(Plan::cost).sum();
+    Reorganisation { plans, total_cost }
+}
+
+/// Prints a packing plan for every rucksack at [path].
+#[allow(dead_code)]
+pub fn report(path: &Path) {
+    let input = fs::read_to_string(path).expect("file not found");
+    let reorganisation = reorganise(&input);
+    for (line, plan) in reorganisation.plans.iter().enumerate() {
+        match plan {
+            Some(plan) => {
+                let moves: Vec<String> = plan
+                    .0
+                    .iter()
+                    .map(|t| format!("{}x{} -> {:?}", t.count, t.item.0, t.to))
+                    .collect();
+                println!("Rucksack {}: {}", line + 1, moves.join(", "));
+            }
+            None => println!("Rucksack {}: can't be fixed", line + 1),
+        }
+    }
+    println!("Total moves: {}", reorganisation.total_cost);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Applies [plan] and checks it leaves disjoint, equally sized compartments.
+    fn check(left: &str, right: &str, plan: &Plan) {
+        let (mut left, mut right) = (count(left), count(right));
+        for transfer in &plan.0 {
+            let t = transfer.item.get_priority() as usize - 1;
+            let (from, to) = match transfer.to {
+                Side::Left => (&mut right, &mut left),
+                Side::Right => (&mut left, &mut right),
+            };
+            from[t] -= transfer.count;
+            to[t] += transfer.count;
+        }
+        assert_eq!(left.iter().sum::<u32>(), right.iter().sum::<u32>());
+        assert!((0..52).all(|t| left[t] == 0 || right[t] == 0));
+    }
+
+    #[test]
+    fn test_plan() {
+        // One `p` crosses over and one item has to swap back to make room.
+        let fixed = plan("vJrwpWtwJgWr", "hcsFMMfFFhFp").unwrap();
+        check("vJrwpWtwJgWr", "hcsFMMfFFhFp", &fixed);
+        assert_eq!(fixed.cost(), 2);
+        assert_eq!(plan("ab", "cd"), Some(Plan(vec![])));
+        // Keeping `a` on the left pulls one over, so `b` moves right.
+        let fixed = plan("aab", "acc").unwrap();
+        check("aab", "acc", &fixed);
+        assert_eq!(fixed.cost(), 2);
+        assert_eq!(plan("aa", "aa"), None);
+    }
+
+    #[test]
+    fn test_reorganise() {
+        let input = fs::read_to_string("src/dec03/testdata.txt").unwrap();
+        let reorganisation = reorganise(&input);
+        for (line, plan) in input.lines().zip(&reorganisation.plans) {
+            let (left, right) = line.split_at(line.len() / 2);
+            check(left, right, plan.as_ref().unwrap());
+        }
+        let input = fs::read_to_string("src/dec03/example.txt").unwrap();
+        assert_eq!(reorganise(&input).total_cost, 18);
+    }
+}
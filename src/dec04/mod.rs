@@ -1,59 +1,111 @@
-use std::{ops::RangeInclusive, path::Path};
+use std::{fmt::Display, ops::RangeInclusive, path::Path};
 
-struct Pair(RangeInclusive<u16>, RangeInclusive<u16>);
+use sections::Sections;
 
-impl From<&str> for Pair {
-    fn from(s: &str) -> Self {
-        fn parse_range(s: &str) -> RangeInclusive<u16> {
-            let mut parts = s.split('-');
-            let start = parts.next().unwrap().parse().unwrap();
-            let end = parts.next().unwrap().parse().unwrap();
-            start..=end
+mod planner;
+mod sections;
+mod sweep;
+
+/// The section assignments on one line, one range per elf.
+struct Group(Vec<RangeInclusive<u64>>);
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    /// A range that isn't two section IDs separated by `-`.
+    Malformed(String),
+    /// A range that ends before it starts, like `7-3`.
+    Reversed(u64, u64),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Malformed(range) => write!(f, "malformed range: {:?}", range),
+            ParseError::Reversed(start, end) => write!(f, "reversed range: {}-{}", start, end),
         }
-        let mut parts = s.split(',');
-        let elf_1 = parse_range(parts.next().unwrap());
-        let elf_2 = parse_range(parts.next().unwrap());
-        Pair(elf_1, elf_2)
     }
 }
 
-fn load(path: &Path) -> Vec<Pair> {
+impl Group {
+    fn sections(&self) -> Vec<Sections> {
+        self.0.iter().map(Sections::from).collect()
+    }
+
+    /// Whether any elf's sections are all covered by another elf.
+    fn fully_contains(&self) -> bool {
+        let sections = self.sections();
+        sections.iter().enumerate().any(|(i, elf_1)| {
+            sections
+                .iter()
+                .enumerate()
+                .any(|(j, elf_2)| i != j && elf_1.contains(elf_2))
+        })
+    }
+
+    /// Whether any two elves share a section.
+    fn overlaps(&self) -> bool {
+        let sections = self.sections();
+        sections.iter().enumerate().any(|(i, elf_1)| {
+            sections[i + 1..]
+                .iter()
+                .any(|elf_2| !elf_1.intersection(elf_2).is_empty())
+        })
+    }
+}
+
+impl TryFrom<&str> for Group {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        fn parse_range(s: &str) -> Result<RangeInclusive<u64>, ParseError> {
+            let malformed = || ParseError::Malformed(s.to_string());
+            let (start, end) = s.split_once('-').ok_or_else(malformed)?;
+            let start: u64 = start.trim().parse().map_err(|_| malformed())?;
+            let end: u64 = end.trim().parse().map_err(|_| malformed())?;
+            if start > end {
+                return Err(ParseError::Reversed(start, end));
+            }
+            Ok(start..=end)
+        }
+        let ranges = s
+            .split(',')
+            .map(parse_range)
+            .collect::<Result<Vec<RangeInclusive<u64>>, ParseError>>()?;
+        Ok(Group(ranges))
+    }
+}
+
+/// Loads the groups at [path], or the first parse error and its line number.
+fn load(path: &Path) -> Result<Vec<Group>, (usize, ParseError)> {
     let input = std::fs::read_to_string(path).expect("file not found");
-    let mut pairs: Vec<Pair> = vec![];
-    for line in input.lines() {
-        pairs.push(Pair::from(line));
+    let mut groups: Vec<Group> = vec![];
+    for (line_no, line) in input.lines().enumerate() {
+        groups.push(Group::try_from(line).map_err(|err| (line_no + 1, err))?);
     }
-    pairs
+    Ok(groups)
+}
+
+/// Every section assigned to anyone in [groups].
+#[allow(dead_code)]
+fn crew_sections(groups: &[Group]) -> Sections {
+    groups
+        .iter()
+        .flat_map(Group::sections)
+        .fold(Sections::default(), |crew, elf| crew.union(&elf))
 }
 
 #[allow(dead_code)]
 pub fn star_1() -> u16 {
-    let pairs = load(Path::new("src/dec04/testdata.txt"));
-    let mut num_pairs = 0;
-    for pair in pairs {
-        let elf_1 = pair.0;
-        let elf_2 = pair.1;
-        if elf_1.start() <= elf_2.start() && elf_1.end() >= elf_2.end() ||
-            elf_2.start() <= elf_1.start() && elf_2.end() >= elf_1.end() {
-            num_pairs += 1;
-        }
-    }
-    num_pairs
+    let groups = load(Path::new("src/dec04/testdata.txt"))
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
+    groups.iter().filter(|group| group.fully_contains()).count() as u16
 }
 
 #[allow(dead_code)]
 pub fn star_2() -> u16 {
-    let pairs = load(Path::new("src/dec04/testdata.txt"));
-    let mut num_pairs = 0;
-    for pair in pairs {
-        let elf_1 = pair.0;
-        let elf_2 = pair.1;
-        if elf_1.start() <= elf_2.end() && elf_1.end() >= elf_2.start() ||
-            elf_2.start() <= elf_1.end() && elf_2.end() >= elf_1.start() {
-            num_pairs += 1;
-        }
-    }
-    num_pairs
+    let groups = load(Path::new("src/dec04/testdata.txt"))
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
+    groups.iter().filter(|group| group.overlaps()).count() as u16
 }
 
 #[cfg(test)]
@@ -69,4 +121,33 @@ mod test {
     fn test_star_2() {
         assert_eq!(star_2(), 811);
     }
+
+    #[test]
+    fn test_crew_sections() {
+        let groups = load(Path::new("src/dec04/example.txt")).unwrap();
+        let crew = crew_sections(&groups);
+        assert_eq!(crew.ranges(), vec![2..=9]);
+        assert_eq!(crew.complement(&(1..=10)).ranges(), vec![1..=1, 10..=10]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let group = Group::try_from("1-3,2-2,10-18446744073709551615").unwrap();
+        assert_eq!(group.0, vec![1..=3, 2..=2, 10..=u64::MAX]);
+        assert!(group.fully_contains());
+        assert!(group.overlaps());
+        assert!(!Group::try_from("1-2,3-4,5-6").unwrap().overlaps());
+        assert_eq!(
+            Group::try_from("2-4,7-3").err(),
+            Some(ParseError::Reversed(7, 3))
+        );
+        assert_eq!(
+            Group::try_from("2-4,x-3").err(),
+            Some(ParseError::Malformed("x-3".to_string()))
+        );
+        assert_eq!(
+            Group::try_from("2-4,").err(),
+            Some(ParseError::Malformed("".to_string()))
+        );
+    }
 }
@@ -0,0 +1,131 @@
+use std::{ops::RangeInclusive, path::Path};
+
+use super::{load, Group};
+
+/// New assignments for a group, one per elf. An elf gets `None` if there are
+/// fewer sections to go round than elves sharing them.
+type Reassignment = Vec<Option<RangeInclusive<u64>>>;
+
+/// Proposes assignments that keep the sections a group covers between them
+/// but stop any two elves overlapping.
+///
+/// Elves whose ranges are chained together by overlaps share out that
+/// stretch of sections as evenly as possible, in order of where their ranges
+/// started. Elves that don't overlap anyone keep their ranges.
+fn reassign(group: &Group) -> Reassignment {
+    let mut order: Vec<usize> = (0..group.0.len()).collect();
+    order.sort_by_key(|&elf| (*group.0[elf].start(), *group.0[elf].end()));
+
+    let mut reassignment = vec![None; group.0.len()];
+    let mut idx = 0;
+    while idx < order.len() {
+        let start = *group.0[order[idx]].start();
+        let mut end = *group.0[order[idx]].end();
+        let mut elves = vec![order[idx]];
+        idx += 1;
+        while idx < order.len() && *group.0[order[idx]].start() <= end {
+            end = end.max(*group.0[order[idx]].end());
+            elves.push(order[idx]);
+            idx += 1;
+        }
+
+        // The first `extra` elves get one section more than the rest.
+        let len = (end - start) as u128 + 1;
+        let (share, extra) = (len / elves.len() as u128, len % elves.len() as u128);
+        let mut next = start as u128;
+        for (position, elf) in elves.into_iter().enumerate() {
+            let size = share + if (position as u128) < extra { 1 } else { 0 };
+            if size > 0 {
+                reassignment[elf] = Some(next as u64..=(next + size - 1) as u64);
+                next += size;
+            }
+        }
+    }
+    reassignment
+}
+
+/// The proposed assignments, and the number of groups [super::star_1] and
+/// [super::star_2] would count before and after applying them.
+#[derive(Debug)]
+struct Plan {
+    reassignments: Vec<Reassignment>,
+    contained_before: usize,
+    contained_after: usize,
+    overlapping_before: usize,
+    overlapping_after: usize,
+}
+
+fn plan(groups: &[Group]) -> Plan {
+    let reassignments: Vec<Reassignment> = groups.iter().map(reassign).collect();
+    let after: Vec<Group> = reassignments
+        .iter()
+        .map(|reassignment| Group(reassignment.iter().flatten().cloned().collect()))
+        .collect();
+    Plan {
+        reassignments,
+        contained_before: groups.iter().filter(|group| group.fully_contains()).count(),
+        contained_after: after.iter().filter(|group| group.fully_contains()).count(),
+        overlapping_before: groups.iter().filter(|group| group.overlaps()).count(),
+        overlapping_after: after.iter().filter(|group| group.overlaps()).count(),
+    }
+}
+
+/// Prints the proposed assignments for the groups at [path].
+#[allow(dead_code)]
+pub fn report(path: &Path) {
+    let groups = load(path).unwrap();
+    let plan = plan(&groups);
+    for (group, reassignment) in groups.iter().zip(&plan.reassignments) {
+        let before: Vec<String> = group
+            .0
+            .iter()
+            .map(|range| format!("{}-{}", range.start(), range.end()))
+            .collect();
+        let after: Vec<String> = reassignment
+            .iter()
+            .map(|range| match range {
+                Some(range) => format!("{}-{}", range.start(), range.end()),
+                None => "-".to_string(),
+            })
+            .collect();
+        println!("{} => {}", before.join(","), after.join(","));
+    }
+    println!(
+        "Fully contained: {} => {}",
+        plan.contained_before, plan.contained_after
+    );
+    println!(
+        "Overlapping: {} => {}",
+        plan.overlapping_before, plan.overlapping_after
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn reassign_str(s: &str) -> Reassignment {
+        reassign(&Group::try_from(s).unwrap())
+    }
+
+    #[test]
+    fn test_reassign() {
+        assert_eq!(reassign_str("2-4,6-8"), vec![Some(2..=4), Some(6..=8)]);
+        assert_eq!(reassign_str("5-7,7-9"), vec![Some(5..=7), Some(8..=9)]);
+        assert_eq!(reassign_str("3-7,2-8"), vec![Some(6..=8), Some(2..=5)]);
+        assert_eq!(reassign_str("6-6,6-6"), vec![Some(6..=6), None]);
+        assert_eq!(
+            reassign_str("1-4,20-21,3-9,5-5"),
+            vec![Some(1..=3), Some(20..=21), Some(4..=6), Some(7..=9)]
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let plan = plan(&load(Path::new("src/dec04/testdata.txt")).unwrap());
+        assert_eq!(plan.contained_before, 305);
+        assert_eq!(plan.overlapping_before, 811);
+        assert_eq!(plan.contained_after, 0);
+        assert_eq!(plan.overlapping_after, 0);
+    }
+}
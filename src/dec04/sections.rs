@@ -0,0 +1,103 @@
+use std::ops::RangeInclusive;
+
+use range_union_find::IntRangeUnionFind;
+
+/// A set of section IDs, stored as disjoint ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(super) struct Sections(IntRangeUnionFind<u64>);
+
+impl Sections {
+    pub(super) fn union(&self, other: &Sections) -> Sections {
+        Sections(&self.0 | &other.0)
+    }
+
+    pub(super) fn intersection(&self, other: &Sections) -> Sections {
+        Sections(&self.0 & &other.0)
+    }
+
+    pub(super) fn difference(&self, other: &Sections) -> Sections {
+        // `IntRangeUnionFind`'s own subtraction trips its internal assertions
+        // on some inputs, so go through the complement instead.
+        self.intersection(&other.complement(&(u64::MIN..=u64::MAX)))
+    }
+
+    /// The sections within [bounds] that aren't in this set.
+    pub(super) fn complement(&self, bounds: &RangeInclusive<u64>) -> Sections {
+        let mut gaps = IntRangeUnionFind::new();
+        let end = *bounds.end() as u128;
+        let mut next = *bounds.start() as u128;
+        for range in self.ranges() {
+            let (range_start, range_end) = (*range.start() as u128, *range.end() as u128);
+            if range_start > end {
+                break;
+            }
+            if range_start > next {
+                gaps.insert_range(&(next as u64..=(range_start - 1) as u64))
+                    .unwrap();
+            }
+            next = next.max(range_end + 1);
+        }
+        if next <= end {
+            gaps.insert_range(&(next as u64..=end as u64)).unwrap();
+        }
+        Sections(gaps)
+    }
+
+    /// Whether every section in [other] is also in this set.
+    pub(super) fn contains(&self, other: &Sections) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.ranges().is_empty()
+    }
+
+    /// The number of sections in the set.
+    #[allow(dead_code)]
+    pub(super) fn len(&self) -> u128 {
+        self.ranges()
+            .iter()
+            .map(|range| (range.end() - range.start()) as u128 + 1)
+            .sum()
+    }
+
+    pub(super) fn ranges(&self) -> Vec<RangeInclusive<u64>> {
+        self.0.to_collection()
+    }
+}
+
+impl From<&RangeInclusive<u64>> for Sections {
+    fn from(range: &RangeInclusive<u64>) -> Self {
+        let mut sections = IntRangeUnionFind::new();
+        if !range.is_empty() {
+            sections.insert_range(range).unwrap();
+        }
+        Sections(sections)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_algebra() {
+        let a = Sections::from(&(2..=6));
+        let b = Sections::from(&(4..=8));
+        assert_eq!(a.union(&b).ranges(), vec![2..=8]);
+        assert_eq!(a.intersection(&b).ranges(), vec![4..=6]);
+        assert_eq!(a.difference(&b).ranges(), vec![2..=3]);
+        assert_eq!(a.complement(&(0..=10)).ranges(), vec![0..=1, 7..=10]);
+        assert_eq!(a.len(), 5);
+
+        let gappy = a.union(&Sections::from(&(10..=12)));
+        assert_eq!(gappy.len(), 8);
+        assert!(gappy.contains(&Sections::from(&(3..=5))));
+        assert!(!gappy.contains(&Sections::from(&(5..=10))));
+        assert!(gappy.contains(&Sections::default()));
+        assert_eq!(
+            Sections::from(&(3..=8)).difference(&gappy).ranges(),
+            vec![7..=8]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::{ops::RangeInclusive, path::Path};
+
+use super::{load, Group};
+
+/// How the whole crew's assignments cover the sections.
+#[derive(Debug, PartialEq, Eq)]
+struct Coverage {
+    /// The most elves assigned to any one section.
+    max_elves: usize,
+    /// The first section with [max_elves] elves assigned.
+    busiest_section: Option<u64>,
+    /// Sections between the lowest and highest assigned ones that nobody is
+    /// assigned to.
+    uncovered: Vec<RangeInclusive<u64>>,
+    /// Pairs of elves, from any lines, whose assignments overlap.
+    overlapping_pairs: usize,
+}
+
+fn analyse(groups: &[Group]) -> Coverage {
+    let ranges: Vec<&RangeInclusive<u64>> = groups.iter().flat_map(|group| &group.0).collect();
+
+    // Each assignment adds an elf at its start and removes one just past its
+    // end, so the number of elves on a section is the running total of the
+    // events up to it.
+    let mut events: Vec<(u128, i64)> = ranges
+        .iter()
+        .flat_map(|range| [(*range.start() as u128, 1), (*range.end() as u128 + 1, -1)])
+        .collect();
+    events.sort();
+    let mut max_elves = 0;
+    let mut busiest_section = None;
+    let mut uncovered = Vec::new();
+    let mut elves: i64 = 0;
+    let mut idx = 0;
+    while idx < events.len() {
+        let section = events[idx].0;
+        while idx < events.len() && events[idx].0 == section {
+            elves += events[idx].1;
+            idx += 1;
+        }
+        if elves as usize > max_elves {
+            max_elves = elves as usize;
+            busiest_section = Some(section as u64);
+        }
+        if elves == 0 {
+            if let Some(&(next, _)) = events.get(idx) {
+                uncovered.push(section as u64..=(next - 1) as u64);
+            }
+        }
+    }
+
+    // Visiting assignments by start, each one overlaps every earlier one that
+    // hasn't ended by the time it starts.
+    let mut starts: Vec<u64> = ranges.iter().map(|range| *range.start()).collect();
+    let mut ends: Vec<u64> = ranges.iter().map(|range| *range.end()).collect();
+    starts.sort();
+    ends.sort();
+    let mut ended = 0;
+    let mut overlapping_pairs = 0;
+    for (started, start) in starts.iter().enumerate() {
+        while ended < ends.len() && ends[ended] < *start {
+            ended += 1;
+        }
+        overlapping_pairs += started - ended;
+    }
+
+    Coverage {
+        max_elves,
+        busiest_section,
+        uncovered,
+        overlapping_pairs,
+    }
+}
+
+/// Prints how the assignments at [path] cover the sections.
+#[allow(dead_code)]
+pub fn report(path: &Path) {
+    let coverage = analyse(&load(path).unwrap());
+    if let Some(section) = coverage.busiest_section {
+        println!("{} elves on section {}", coverage.max_elves, section);
+    }
+    for range in &coverage.uncovered {
+        println!("Nobody on sections {}-{}", range.start(), range.end());
+    }
+    println!("{} overlapping pairs", coverage.overlapping_pairs);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn brute_force_overlaps(groups: &[Group]) -> usize {
+        let ranges: Vec<&RangeInclusive<u64>> = groups.iter().flat_map(|group| &group.0).collect();
+        let mut count = 0;
+        for (idx, a) in ranges.iter().enumerate() {
+            for b in &ranges[idx + 1..] {
+                if a.start() <= b.end() && b.start() <= a.end() {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_example() {
+        let groups = load(Path::new("src/dec04/example.txt")).unwrap();
+        let coverage = analyse(&groups);
+        assert_eq!(coverage.max_elves, 8);
+        assert_eq!(coverage.busiest_section, Some(6));
+        assert!(coverage.uncovered.is_empty());
+        assert_eq!(coverage.overlapping_pairs, brute_force_overlaps(&groups));
+    }
+
+    #[test]
+    fn test_gaps() {
+        let groups = vec![
+            Group::try_from("1-2,8-9").unwrap(),
+            Group::try_from("4-4,9-12,18446744073709551615-18446744073709551615").unwrap(),
+        ];
+        let coverage = analyse(&groups);
+        assert_eq!(coverage.max_elves, 2);
+        assert_eq!(coverage.busiest_section, Some(9));
+        assert_eq!(coverage.uncovered, vec![3..=3, 5..=7, 13..=u64::MAX - 1]);
+        assert_eq!(coverage.overlapping_pairs, 1);
+    }
+
+    #[test]
+    fn test_testdata() {
+        let groups = load(Path::new("src/dec04/testdata.txt")).unwrap();
+        assert_eq!(
+            analyse(&groups).overlapping_pairs,
+            brute_force_overlaps(&groups)
+        );
+    }
+}
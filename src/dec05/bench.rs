@@ -0,0 +1,87 @@
+//! Compares moving blocks in a [Yard] against moving crates one at a time.
+//!
+//! Run with `cargo test --release dec05::bench -- --ignored --nocapture`.
+
+use std::{collections::VecDeque, time::Instant};
+
+use super::{
+    crane::{Crane, CrateMover9000, CrateMover9001},
+    tops, Move, Problem, Stack, Tops,
+};
+use crate::random::Random;
+
+/// Generates [crates] crates spread over nine stacks and [moves] legal moves
+/// between different stacks, each taking up to half of the stack it's from.
+fn generate(crates: usize, moves: usize) -> Problem {
+    let mut random = Random::new(0x2022_1205);
+    let mut next = |bound| random.below(bound);
+    let mut stacks = vec![Stack(VecDeque::new()); 9];
+    for _ in 0..crates {
+        let value = (b'A' + next(26) as u8) as char;
+        stacks[next(9)].0.push_back(value);
+    }
+    let mut heights: Vec<usize> = stacks.iter().map(|stack| stack.0.len()).collect();
+    let mut procedure = Vec::new();
+    for line in 0..moves {
+        let from = next(9);
+        let to = (from + 1 + next(8)) % 9;
+        let count = next(heights[from] / 2 + 1);
+        heights[from] -= count;
+        heights[to] += count;
+        procedure.push(Move {
+            count,
+            from: from + 1,
+            to: to + 1,
+            line,
+        });
+    }
+    Problem {
+        stacks,
+        moves: procedure,
+    }
+}
+
+/// The original way of running a procedure, one crate at a time.
+fn one_at_a_time(problem: &Problem, crane: &dyn Crane) -> Tops {
+    let mut stacks = problem.stacks.clone();
+    for m in &problem.moves {
+        m.apply(&mut stacks, crane);
+    }
+    tops(&stacks)
+}
+
+#[test]
+fn test_matches_one_at_a_time() {
+    let problem = generate(1_000, 1_000);
+    for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+        assert_eq!(
+            problem.clone().process(crane).unwrap(),
+            one_at_a_time(&problem, crane)
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn bench_large_input() {
+    let problem = generate(1_000_000, 10_000);
+    for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+        let start = Instant::now();
+        let blocks = problem.clone().process(crane).unwrap();
+        let blocks_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let crates = one_at_a_time(&problem, crane);
+        let crates_elapsed = start.elapsed();
+
+        assert_eq!(blocks, crates);
+        println!(
+            "{}: {} moves, blocks {:?}, one at a time {:?} ({:.1}x)",
+            crane.name(),
+            problem.moves.len(),
+            blocks_elapsed,
+            crates_elapsed,
+            crates_elapsed.as_secs_f64() / blocks_elapsed.as_secs_f64()
+        );
+    }
+}
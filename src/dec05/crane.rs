@@ -0,0 +1,270 @@
+use std::{ops::AddAssign, path::Path};
+
+use super::{load, IllegalMove, Problem, Tops};
+
+/// A model of crane. A move is carried out as a series of lifts, each of
+/// which takes a block of crates off the top of one stack and sets it down
+/// on top of another.
+pub(super) trait Crane {
+    fn name(&self) -> String;
+
+    /// The sizes of the lifts the crane makes to move [count] crates, in
+    /// order. They must add up to [count].
+    fn lifts(&self, count: usize) -> Vec<usize>;
+
+    /// Rearranges a lifted [block], given top crate first, into the order it
+    /// is set down in, top crate first.
+    fn set_down(&self, _block: &mut [char]) {}
+
+    /// The energy a lift of [size] crates uses.
+    fn energy(&self, _size: usize) -> u64 {
+        0
+    }
+
+    /// Whether a whole move between two different stacks always leaves the
+    /// block it moves upside down (`Some(true)`) or the right way up
+    /// (`Some(false)`), however big it is. Lets a [super::yard::Yard] make
+    /// the move in one go.
+    fn flips(&self) -> Option<bool> {
+        None
+    }
+}
+
+/// Lifts one crate at a time.
+pub(super) struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_string()
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        vec![1; count]
+    }
+
+    fn flips(&self) -> Option<bool> {
+        Some(true)
+    }
+}
+
+/// Lifts every crate in a move at once.
+pub(super) struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_string()
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        if count == 0 {
+            Vec::new()
+        } else {
+            vec![count]
+        }
+    }
+
+    fn flips(&self) -> Option<bool> {
+        Some(false)
+    }
+}
+
+/// Lifts up to [capacity] crates at once, so big moves are made in batches.
+pub(super) struct Capacity(usize);
+
+impl Capacity {
+    pub(super) fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "a crane must lift at least one crate at a time"
+        );
+        Capacity(capacity)
+    }
+}
+
+impl Crane for Capacity {
+    fn name(&self) -> String {
+        format!("capacity {}", self.0)
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        let mut lifts = vec![self.0; count / self.0];
+        if !count.is_multiple_of(self.0) {
+            lifts.push(count % self.0);
+        }
+        lifts
+    }
+}
+
+/// Lifts every crate in a move at once and turns the block upside down
+/// before setting it down.
+pub(super) struct Rotating;
+
+impl Crane for Rotating {
+    fn name(&self) -> String {
+        "rotating".to_string()
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        CrateMover9001.lifts(count)
+    }
+
+    fn set_down(&self, block: &mut [char]) {
+        block.reverse();
+    }
+
+    fn flips(&self) -> Option<bool> {
+        Some(true)
+    }
+}
+
+/// Moves crates like [crane], using [per_lift] energy for each lift plus
+/// [per_crate] for each crate lifted.
+pub(super) struct Metered<C: Crane> {
+    pub(super) crane: C,
+    pub(super) per_lift: u64,
+    pub(super) per_crate: u64,
+}
+
+impl<C: Crane> Crane for Metered<C> {
+    fn name(&self) -> String {
+        format!("metered {}", self.crane.name())
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        self.crane.lifts(count)
+    }
+
+    fn set_down(&self, block: &mut [char]) {
+        self.crane.set_down(block)
+    }
+
+    fn energy(&self, size: usize) -> u64 {
+        self.per_lift + self.per_crate * size as u64
+    }
+
+    fn flips(&self) -> Option<bool> {
+        self.crane.flips()
+    }
+}
+
+/// What carrying out some moves took.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Cost {
+    pub(super) lifts: usize,
+    pub(super) crates_lifted: usize,
+    pub(super) energy: u64,
+}
+
+impl AddAssign for Cost {
+    fn add_assign(&mut self, rhs: Self) {
+        self.lifts += rhs.lifts;
+        self.crates_lifted += rhs.crates_lifted;
+        self.energy += rhs.energy;
+    }
+}
+
+/// The cost of each move, by line number, and the tops afterwards.
+type Accounts = (Vec<(usize, Cost)>, Tops);
+
+/// What each move in [problem] costs with [crane]. Stops at the first
+/// illegal move.
+fn account(problem: &Problem, crane: &dyn Crane) -> Result<Accounts, (usize, IllegalMove)> {
+    let mut stacks = problem.stacks.clone();
+    let mut costs = Vec::new();
+    for m in &problem.moves {
+        m.check(&stacks).map_err(|err| (m.line, err))?;
+        costs.push((m.line, m.apply(&mut stacks, crane)));
+    }
+    Ok((costs, super::tops(&stacks)))
+}
+
+/// Prints what the moves at [path] cost with each model of crane. Each line
+/// of the procedure is listed too if [per_move] is set.
+#[allow(dead_code)]
+pub fn report(path: &Path, per_move: bool) {
+    let problem = load(path);
+    let cranes: Vec<Box<dyn Crane>> = vec![
+        Box::new(CrateMover9000),
+        Box::new(CrateMover9001),
+        Box::new(Capacity::new(3)),
+        Box::new(Rotating),
+        Box::new(Metered {
+            crane: CrateMover9001,
+            per_lift: 10,
+            per_crate: 1,
+        }),
+    ];
+    for crane in cranes {
+        let (costs, tops) = account(&problem, crane.as_ref())
+            .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
+        let mut total = Cost::default();
+        for (line_no, cost) in costs {
+            if per_move {
+                println!(
+                    "  line {}: {} lifts, {} crates, {} energy",
+                    line_no, cost.lifts, cost.crates_lifted, cost.energy
+                );
+            }
+            total += cost;
+        }
+        println!(
+            "{}: {} ({} lifts, {} crates, {} energy)",
+            crane.name(),
+            tops,
+            total.lifts,
+            total.crates_lifted,
+            total.energy
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn total(crane: &dyn Crane) -> (Cost, String) {
+        let problem = load(Path::new("src/dec05/example.txt"));
+        let (costs, tops) = account(&problem, crane).unwrap();
+        let mut total = Cost::default();
+        for (_, cost) in costs {
+            total += cost;
+        }
+        (total, tops.to_string())
+    }
+
+    #[test]
+    fn test_models() {
+        let cost = |lifts, crates_lifted, energy| Cost {
+            lifts,
+            crates_lifted,
+            energy,
+        };
+        assert_eq!(total(&CrateMover9000), (cost(7, 7, 0), "CMZ".to_string()));
+        assert_eq!(total(&CrateMover9001), (cost(4, 7, 0), "MCD".to_string()));
+        // Rotating a whole block puts it down the way the 9000 would.
+        assert_eq!(total(&Rotating), (cost(4, 7, 0), "CMZ".to_string()));
+        // The three crates moved in one go are split into two batches.
+        assert_eq!(total(&Capacity::new(2)), (cost(5, 7, 0), "MCZ".to_string()));
+        assert_eq!(
+            total(&Metered {
+                crane: CrateMover9000,
+                per_lift: 5,
+                per_crate: 2,
+            }),
+            (cost(7, 7, 49), "CMZ".to_string())
+        );
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(Capacity::new(3).lifts(7), vec![3, 3, 1]);
+        assert_eq!(Capacity::new(3).lifts(6), vec![3, 3]);
+        assert!(Capacity::new(3).lifts(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one crate")]
+    fn test_no_capacity() {
+        Capacity::new(0);
+    }
+}
@@ -1,15 +1,36 @@
-use std::{collections::VecDeque, fmt::Write, fs, path::Path};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Write},
+    fs,
+    path::Path,
+};
 
 use regex::Regex;
 
-#[derive(Debug, Clone)]
+use self::{
+    crane::{Cost, Crane, CrateMover9000, CrateMover9001},
+    yard::Yard,
+};
+
+#[cfg(test)]
+mod bench;
+mod crane;
+mod render;
+mod replay;
+mod solver;
+mod yard;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Stack(VecDeque<char>);
 
 #[derive(Debug, Clone)]
 struct Move {
     count: usize,
+    /// The stacks the move is between, numbered from 1 as in the input.
     from: usize,
     to: usize,
+    /// The line of the input the move was read from.
+    line: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -18,32 +39,165 @@ struct Problem {
     moves: Vec<Move>,
 }
 
-enum CrateMoverVersion {
-    CrateMover9000,
-    CrateMover9001,
+/// Why a move can't be carried out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum IllegalMove {
+    /// The move refers to a stack that doesn't exist, numbered from 1.
+    NoSuchStack(usize),
+    /// The move takes more crates than the stack, numbered from 1, holds.
+    NotEnoughCrates {
+        stack: usize,
+        wanted: usize,
+        available: usize,
+    },
+}
+
+impl Display for IllegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IllegalMove::NoSuchStack(stack) => write!(f, "there is no stack {}", stack),
+            IllegalMove::NotEnoughCrates {
+                stack,
+                wanted,
+                available,
+            } => write!(
+                f,
+                "stack {} holds {} crates but {} were moved",
+                stack, available, wanted
+            ),
+        }
+    }
+}
+
+impl Move {
+    /// Checks that the move can be carried out on [stacks]. Whether it can
+    /// doesn't depend on the crane, as they all move the same number of crates.
+    fn check(&self, stacks: &[Stack]) -> Result<(), IllegalMove> {
+        self.check_heights(stacks.len(), |stack| stacks[stack].0.len())
+    }
+
+    /// Like [Move::check], given how many stacks there are and how tall each
+    /// one is.
+    fn check_heights(
+        &self,
+        stacks: usize,
+        height: impl Fn(usize) -> usize,
+    ) -> Result<(), IllegalMove> {
+        for stack in [self.from, self.to] {
+            if stack == 0 || stack > stacks {
+                return Err(IllegalMove::NoSuchStack(stack));
+            }
+        }
+        let available = height(self.from - 1);
+        if available < self.count {
+            return Err(IllegalMove::NotEnoughCrates {
+                stack: self.from,
+                wanted: self.count,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// The indices of the stacks the move is from and to. Panics on a stack
+    /// 0, so only call it once the move passes [Move::check].
+    fn indices(&self) -> (usize, usize) {
+        (self.from - 1, self.to - 1)
+    }
+
+    /// Moves crates between [stacks] with [crane], returning what it cost.
+    /// Panics if the move doesn't pass [Move::check].
+    fn apply(&self, stacks: &mut [Stack], crane: &dyn Crane) -> Cost {
+        let (from, to) = self.indices();
+        let mut cost = Cost::default();
+        for size in crane.lifts(self.count) {
+            let mut block: Vec<char> = stacks[from].0.drain(..size).collect();
+            crane.set_down(&mut block);
+            let to = stacks.get_mut(to).unwrap();
+            for c in block.into_iter().rev() {
+                to.0.push_front(c);
+            }
+            cost += Cost {
+                lifts: 1,
+                crates_lifted: size,
+                energy: crane.energy(size),
+            };
+        }
+        cost
+    }
+}
+
+/// The crate on top of each stack, or `None` for an empty one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tops(Vec<Option<char>>);
+
+/// Stands in for the top crate of an empty stack when tops are displayed.
+const EMPTY: char = '-';
+
+impl Display for Tops {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for top in &self.0 {
+            f.write_char(top.unwrap_or(EMPTY))?;
+        }
+        Ok(())
+    }
+}
+
+fn tops(stacks: &[Stack]) -> Tops {
+    Tops(
+        stacks
+            .iter()
+            .map(|stack| stack.0.front().copied())
+            .collect(),
+    )
 }
 
 impl Problem {
-    /// Runs the procedure specified by [moves] according to the rules of [version].
-    fn process(&mut self, version: CrateMoverVersion) -> String {
+    /// Runs the procedure specified by [moves] with [crane].
+    /// Stops at the first illegal move, returning its line number.
+    fn process(&mut self, crane: &dyn Crane) -> Result<Tops, (usize, IllegalMove)> {
+        let mut yard = Yard::new(&self.stacks);
+        let mut heights = yard.heights();
         for m in &self.moves {
-            let mut to_move = Vec::new();
-            let from = self.stacks.get_mut(m.from).unwrap();
-            for _ in 0..m.count {
-                to_move.push(from.0.pop_front().unwrap());
+            let checked = m.check_heights(heights.len(), |stack| heights[stack]);
+            if let Err(err) = checked {
+                self.stacks = yard.stacks();
+                return Err((m.line, err));
             }
-            let to = self.stacks.get_mut(m.to).unwrap();
-            if let CrateMoverVersion::CrateMover9001 = version {
-                to_move.reverse();
-            }
-            for c in to_move {
-                to.0.push_front(c);
+            yard.apply(m, crane);
+            let (from, to) = m.indices();
+            heights[from] -= m.count;
+            heights[to] += m.count;
+        }
+        self.stacks = yard.stacks();
+        Ok(tops(&self.stacks))
+    }
+
+    /// Like [Problem::process], but skips illegal moves and carries on,
+    /// returning them with their line numbers alongside the tops.
+    fn process_lenient(&mut self, crane: &dyn Crane) -> (Tops, Vec<(usize, IllegalMove)>) {
+        let mut yard = Yard::new(&self.stacks);
+        let mut heights = yard.heights();
+        let mut skipped = Vec::new();
+        for m in &self.moves {
+            match m.check_heights(heights.len(), |stack| heights[stack]) {
+                Ok(()) => {
+                    yard.apply(m, crane);
+                    let (from, to) = m.indices();
+                    heights[from] -= m.count;
+                    heights[to] += m.count;
+                }
+                Err(err) => skipped.push((m.line, err)),
             }
         }
-        self.stacks.iter().fold(String::new(), |mut accum, stack| {
-            accum.write_char(*stack.0.front().unwrap()).unwrap();
-            accum
-        })
+        self.stacks = yard.stacks();
+        (tops(&self.stacks), skipped)
+    }
+
+    /// Every illegal move with its line number, assuming the ones before it
+    /// were skipped.
+    fn validate(&self) -> Vec<(usize, IllegalMove)> {
+        self.clone().process_lenient(&CrateMover9000).1
     }
 }
 
@@ -51,11 +205,19 @@ fn load(path: &Path) -> Problem {
     let input = fs::read_to_string(path).expect("file not found");
     let mut stacks = Vec::new();
     let crate_matcher = Regex::new(r"(\[\w\]|\s{3})\s?").unwrap();
-    let mut lines = input.lines();
-    for line in lines.by_ref() {
+    let mut lines = input.lines().enumerate();
+    for (_, line) in lines.by_ref() {
         if line.trim().is_empty() {
             break;
         }
+        if line.trim_start().starts_with(|c: char| c.is_ascii_digit()) {
+            // The base line numbers every stack, including empty ones.
+            let count = line.split_whitespace().count();
+            if stacks.len() < count {
+                stacks.resize(count, Stack(VecDeque::new()));
+            }
+            continue;
+        }
         for (stack, m) in crate_matcher.captures_iter(line).enumerate() {
             match m.get(0).unwrap().as_str().trim() {
                 "" => {}
@@ -75,28 +237,61 @@ fn load(path: &Path) -> Problem {
 
     let move_matcher = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
     let mut moves = Vec::new();
-    for line in lines {
+    for (idx, line) in lines {
         for m in move_matcher.captures_iter(line) {
             moves.push(Move {
                 count: m[1].parse().unwrap(),
-                from: m[2].parse::<usize>().unwrap() - 1,
-                to: m[3].parse::<usize>().unwrap() - 1,
+                from: m[2].parse().unwrap(),
+                to: m[3].parse().unwrap(),
+                line: idx + 1,
             })
         }
     }
     Problem { stacks, moves }
 }
 
+fn crate_mover(model: u16) -> Box<dyn Crane> {
+    match model {
+        9000 => Box::new(CrateMover9000),
+        9001 => Box::new(CrateMover9001),
+        _ => panic!("unknown CrateMover model: {}", model),
+    }
+}
+
+/// Steps through the moves at [path] interactively using the CrateMover
+/// [model], either 9000 or 9001.
+pub fn replay(path: &Path, model: u16) {
+    replay::run(path, crate_mover(model));
+}
+
 #[allow(dead_code)]
 pub fn star_1() -> String {
     let mut problem = load(Path::new("src/dec05/testdata.txt"));
-    problem.process(CrateMoverVersion::CrateMover9000)
+    problem
+        .process(&CrateMover9000)
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err))
+        .to_string()
 }
 
 #[allow(dead_code)]
 pub fn star_2() -> String {
     let mut problem = load(Path::new("src/dec05/testdata.txt"));
-    problem.process(CrateMoverVersion::CrateMover9001)
+    problem
+        .process(&CrateMover9001)
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err))
+        .to_string()
+}
+
+/// Prints every illegal move in the procedure at [path], then the tops
+/// after running it with the CrateMover [model] and skipping them.
+#[allow(dead_code)]
+pub fn report(path: &Path, model: u16) {
+    let mut problem = load(path);
+    for (line_no, err) in problem.validate() {
+        println!("line {}: {}", line_no, err);
+    }
+    let (tops, _) = problem.process_lenient(crate_mover(model).as_ref());
+    println!("{}", tops);
 }
 
 #[cfg(test)]
@@ -112,4 +307,31 @@ mod test {
     fn test_star_2() {
         assert_eq!(star_2(), String::from("VLCWHTDSZ"));
     }
+
+    #[test]
+    fn test_illegal_moves() {
+        let mut problem = load(Path::new("src/dec05/illegal.txt"));
+        let illegal = vec![
+            (6, IllegalMove::NoSuchStack(4)),
+            (
+                7,
+                IllegalMove::NotEnoughCrates {
+                    stack: 2,
+                    wanted: 4,
+                    available: 3,
+                },
+            ),
+            (8, IllegalMove::NoSuchStack(0)),
+            (10, IllegalMove::NoSuchStack(4)),
+        ];
+        assert_eq!(problem.validate(), illegal);
+        assert_eq!(
+            problem.clone().process(&CrateMover9000),
+            Err(illegal[0].clone())
+        );
+        assert_eq!(
+            problem.process_lenient(&CrateMover9000),
+            (Tops(vec![None, Some('C'), Some('D')]), illegal)
+        );
+    }
 }
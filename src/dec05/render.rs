@@ -0,0 +1,87 @@
+use std::{
+    fmt::{Display, Write},
+    path::Path,
+};
+
+use super::{crane::Crane, load, Move, Problem, Stack};
+
+/// Draws [stacks] the way the puzzle input does, tops first, with the
+/// numbered base line underneath.
+pub(super) fn draw(stacks: &[Stack]) -> String {
+    let height = stacks.iter().map(|stack| stack.0.len()).max().unwrap_or(0);
+    let mut drawing = String::new();
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks
+            .iter()
+            .map(|stack| {
+                // Stacks store their top crate at the front.
+                match stack.0.len().checked_sub(level + 1) {
+                    Some(idx) => format!("[{}]", stack.0[idx]),
+                    None => "   ".to_string(),
+                }
+            })
+            .collect();
+        writeln!(drawing, "{}", row.join(" ")).unwrap();
+    }
+    let base: Vec<String> = (1..=stacks.len()).map(|n| format!(" {} ", n)).collect();
+    writeln!(drawing, "{}", base.join(" ")).unwrap();
+    drawing
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} from {} to {}", self.count, self.from, self.to)
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", draw(&self.stacks))?;
+        writeln!(f)?;
+        for m in &self.moves {
+            writeln!(f, "{}", m)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws the stacks at [path] after running every move with [crane].
+#[allow(dead_code)]
+pub(super) fn final_arrangement(path: &Path, crane: &dyn Crane) -> String {
+    let mut problem = load(path);
+    problem
+        .process(crane)
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
+    draw(&problem.stacks)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::super::crane::CrateMover9000;
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for path in [
+            "src/dec05/example.txt",
+            "src/dec05/illegal.txt",
+            "src/dec05/testdata.txt",
+        ] {
+            let mut input = fs::read_to_string(path).unwrap();
+            if !input.ends_with('\n') {
+                input.push('\n');
+            }
+            assert_eq!(load(Path::new(path)).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_final_arrangement() {
+        assert_eq!(
+            final_arrangement(Path::new("src/dec05/example.txt"), &CrateMover9000),
+            "        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3 \n"
+        );
+    }
+}
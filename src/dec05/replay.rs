@@ -0,0 +1,166 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use super::{crane::Crane, load, render::draw, Problem, Stack};
+
+/// Steps through a problem's moves one at a time, in either direction.
+struct Replay {
+    problem: Problem,
+    crane: Box<dyn Crane>,
+    stacks: Vec<Stack>,
+    /// The crates each applied move took, top first, as they were before
+    /// the crane rearranged them, so any move can be undone.
+    taken: Vec<Vec<char>>,
+    /// The number of moves applied to [stacks] so far.
+    position: usize,
+}
+
+impl Replay {
+    fn new(problem: Problem, crane: Box<dyn Crane>) -> Self {
+        let stacks = problem.stacks.clone();
+        Replay {
+            problem,
+            crane,
+            stacks,
+            taken: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Applies the next move, or returns `false` if there are none left.
+    fn step_forward(&mut self) -> bool {
+        match self.problem.moves.get(self.position) {
+            Some(m) => {
+                let (from, _) = m.indices();
+                let block = self.stacks[from].0.iter().take(m.count).copied();
+                self.taken.push(block.collect());
+                m.apply(&mut self.stacks, self.crane.as_ref());
+                self.position += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Undoes the last move, or returns `false` if none have been applied.
+    fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        let m = &self.problem.moves[self.position];
+        let (from, to) = m.indices();
+        self.stacks[to].0.drain(..m.count);
+        for c in self.taken.pop().unwrap().into_iter().rev() {
+            self.stacks[from].0.push_front(c);
+        }
+        true
+    }
+
+    /// Steps forwards or backwards until exactly [position] moves have been
+    /// applied, stopping early at either end of the procedure.
+    fn jump_to(&mut self, position: usize) {
+        while self.position < position && self.step_forward() {}
+        while self.position > position && self.step_back() {}
+    }
+
+    fn stacks(&self) -> &[Stack] {
+        &self.stacks
+    }
+}
+
+/// Replays the problem at [path] interactively. Reads commands from stdin:
+/// `n` steps forward, `b` steps back, `j <N>` jumps to just after move `N`,
+/// `p` prints the stacks and `q` quits. Lists the illegal moves instead if
+/// there are any.
+pub(super) fn run(path: &Path, crane: Box<dyn Crane>) {
+    let problem = load(path);
+    let illegal = problem.validate();
+    if !illegal.is_empty() {
+        for (line_no, err) in illegal {
+            println!("line {}: {}", line_no, err);
+        }
+        return;
+    }
+    let mut replay = Replay::new(problem, crane);
+    let total = replay.problem.moves.len();
+    let stdin = io::stdin();
+    print!("{}", draw(replay.stacks()));
+    loop {
+        print!("[{}/{}]> ", replay.position, total);
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["n"] => {
+                if let Some(m) = replay.problem.moves.get(replay.position) {
+                    println!("{}", m);
+                }
+                replay.step_forward();
+            }
+            ["b"] => {
+                replay.step_back();
+            }
+            ["j", n] => match n.parse() {
+                Ok(n) => replay.jump_to(n),
+                Err(_) => println!("expected a move number: {}", n),
+            },
+            ["p"] => {}
+            ["q"] => break,
+            _ => {
+                println!("commands: n, b, j <N>, p, q");
+                continue;
+            }
+        }
+        print!("{}", draw(replay.stacks()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{
+        crane::{Capacity, Rotating},
+        crate_mover, tops,
+    };
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        let cranes: [Box<dyn Crane>; 4] = [
+            crate_mover(9000),
+            crate_mover(9001),
+            Box::new(Capacity::new(3)),
+            Box::new(Rotating),
+        ];
+        for crane in cranes {
+            let problem = load(Path::new("src/dec05/testdata.txt"));
+            let start = draw(&problem.stacks);
+            let total = problem.moves.len();
+            let mut replay = Replay::new(problem.clone(), crane);
+            replay.jump_to(total);
+            let finished = tops(replay.stacks());
+            assert_eq!(
+                finished,
+                problem.clone().process(replay.crane.as_ref()).unwrap()
+            );
+
+            replay.jump_to(total / 2);
+            let halfway = draw(replay.stacks());
+            assert!(replay.step_forward());
+            assert!(replay.step_back());
+            assert_eq!(draw(replay.stacks()), halfway);
+
+            replay.jump_to(0);
+            assert!(!replay.step_back());
+            assert_eq!(draw(replay.stacks()), start);
+            replay.jump_to(usize::MAX);
+            assert_eq!(replay.position, total);
+            assert_eq!(tops(replay.stacks()), finished);
+        }
+    }
+}
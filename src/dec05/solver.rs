@@ -0,0 +1,309 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::Path,
+};
+
+use super::{crane::Crane, crate_mover, load, Move, Problem, Stack};
+
+/// The most arrangements the exact search visits before falling back to
+/// the heuristic.
+const MAX_STATES: usize = 50_000;
+
+#[derive(Debug)]
+pub(super) enum Solution {
+    /// A shortest sequence of moves.
+    Exact(Vec<Move>),
+    /// A sequence of moves that isn't necessarily the shortest.
+    Heuristic(Vec<Move>),
+    /// The target can't be reached.
+    Unreachable,
+    /// The instance was too big to search and the heuristic needs at least
+    /// three stacks, so whether the target is reachable is unknown.
+    Unknown,
+}
+
+/// Finds moves that turn [start] into [target] with [crane]. Searches every
+/// arrangement if there are few enough, and otherwise builds the target one
+/// crate at a time.
+pub(super) fn solve(start: &[Stack], target: &[Stack], crane: &dyn Crane) -> Solution {
+    let mut have: Vec<char> = start.iter().flat_map(|stack| stack.0.clone()).collect();
+    let mut want: Vec<char> = target.iter().flat_map(|stack| stack.0.clone()).collect();
+    have.sort_unstable();
+    want.sort_unstable();
+    if start.len() != target.len() || have != want {
+        return Solution::Unreachable;
+    }
+    if let Some(solution) = search(start, target, crane) {
+        return solution;
+    }
+    if start.len() < 3 {
+        return Solution::Unknown;
+    }
+    Solution::Heuristic(merge(start, &construct(start, target), crane))
+}
+
+/// Breadth-first search over arrangements, or `None` if there are more than
+/// [MAX_STATES] of them to visit.
+fn search(start: &[Stack], target: &[Stack], crane: &dyn Crane) -> Option<Solution> {
+    // Each arrangement maps to the one it was reached from and the move.
+    let mut previous: HashMap<Vec<Stack>, Option<(Vec<Stack>, Move)>> = HashMap::new();
+    previous.insert(start.to_vec(), None);
+    let mut queue = VecDeque::from([start.to_vec()]);
+    while let Some(stacks) = queue.pop_front() {
+        if stacks == target {
+            let mut moves = Vec::new();
+            let mut current = stacks;
+            while let Some((before, m)) = previous[&current].clone() {
+                moves.push(m);
+                current = before;
+            }
+            moves.reverse();
+            return Some(Solution::Exact(moves));
+        }
+        for from in 0..stacks.len() {
+            for to in 0..stacks.len() {
+                for count in 1..=stacks[from].0.len() {
+                    let m = Move {
+                        count,
+                        from: from + 1,
+                        to: to + 1,
+                        line: 0,
+                    };
+                    let mut next = stacks.clone();
+                    m.apply(&mut next, crane);
+                    if !previous.contains_key(&next) {
+                        if previous.len() == MAX_STATES {
+                            return None;
+                        }
+                        previous.insert(next.clone(), Some((stacks.clone(), m)));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+    }
+    Some(Solution::Unreachable)
+}
+
+/// Single-crate moves, as `(from, to)` pairs, that build each stack of
+/// [target] in turn from the bottom up. Every crane moves a single crate the
+/// same way, so these work for all of them.
+///
+/// Crates in the way are put on any other stack, including ones already
+/// built, above their finished part. Needs at least three stacks so there
+/// is always somewhere to put them.
+fn construct(start: &[Stack], target: &[Stack]) -> Vec<(usize, usize)> {
+    // Bottom crate first, so the top of a stack is its last element.
+    let mut stacks: Vec<Vec<char>> = start
+        .iter()
+        .map(|stack| stack.0.iter().rev().cloned().collect())
+        .collect();
+    let target: Vec<Vec<char>> = target
+        .iter()
+        .map(|stack| stack.0.iter().rev().cloned().collect())
+        .collect();
+    let mut moves = Vec::new();
+    let mut shift = |stacks: &mut Vec<Vec<char>>, from: usize, to: usize| {
+        let c = stacks[from].pop().unwrap();
+        stacks[to].push(c);
+        moves.push((from, to));
+    };
+    // Somewhere other than [building] and [from] to put a crate, preferring
+    // stacks that haven't been built yet.
+    let spare = |building: usize, from: usize| {
+        (building + 1..target.len())
+            .chain(0..building)
+            .find(|&stack| stack != from)
+            .unwrap()
+    };
+
+    for building in 0..target.len() {
+        // Keep whatever is already right at the bottom.
+        while !target[building].starts_with(&stacks[building]) {
+            shift(&mut stacks, building, spare(building, building));
+        }
+        while stacks[building].len() < target[building].len() {
+            let wanted = target[building][stacks[building].len()];
+            // The reachable copy of the crate with the fewest crates on top.
+            let (from, depth) = (0..stacks.len())
+                .filter(|&stack| stack != building)
+                .filter_map(|stack| {
+                    let floor = if stack < building {
+                        target[stack].len()
+                    } else {
+                        0
+                    };
+                    stacks[stack][floor..]
+                        .iter()
+                        .rev()
+                        .position(|&c| c == wanted)
+                        .map(|depth| (stack, depth))
+                })
+                .min_by_key(|&(_, depth)| depth)
+                .unwrap();
+            for _ in 0..depth {
+                shift(&mut stacks, from, spare(building, from));
+            }
+            shift(&mut stacks, from, building);
+        }
+    }
+    moves
+}
+
+/// Turns single-crate [moves] from [start] into [Move]s, combining runs
+/// between the same stacks wherever [crane] can do them in one go.
+fn merge(start: &[Stack], moves: &[(usize, usize)], crane: &dyn Crane) -> Vec<Move> {
+    let mut stacks = start.to_vec();
+    let mut merged = Vec::new();
+    let mut idx = 0;
+    while idx < moves.len() {
+        let run = moves[idx..]
+            .iter()
+            .take_while(|&&step| step == moves[idx])
+            .count();
+        let (from, to) = moves[idx];
+        let single = |count| Move {
+            count,
+            from: from + 1,
+            to: to + 1,
+            line: 0,
+        };
+        let mut one_by_one = stacks.clone();
+        for _ in 0..run {
+            single(1).apply(&mut one_by_one, crane);
+        }
+        let mut at_once = stacks.clone();
+        single(run).apply(&mut at_once, crane);
+        if at_once == one_by_one {
+            merged.push(single(run));
+        } else {
+            merged.extend((0..run).map(|_| single(1)));
+        }
+        stacks = one_by_one;
+        idx += run;
+    }
+    merged
+}
+
+/// A puzzle that starts from [stacks] and runs [moves], numbered by the
+/// lines they'd be on when written out.
+pub(super) fn puzzle(stacks: Vec<Stack>, moves: Vec<Move>) -> Problem {
+    // The drawing, its base line and the blank line come first.
+    let height = stacks.iter().map(|stack| stack.0.len()).max().unwrap_or(0);
+    let moves = moves
+        .into_iter()
+        .enumerate()
+        .map(|(idx, m)| Move {
+            line: height + idx + 3,
+            ..m
+        })
+        .collect();
+    Problem { stacks, moves }
+}
+
+/// Prints a puzzle that rearranges the stacks at [path] into where its moves
+/// leave them with the CrateMover [model], using as few moves as can be
+/// found.
+#[allow(dead_code)]
+pub fn shortcut(path: &Path, model: u16) {
+    let problem = load(path);
+    let crane = crate_mover(model);
+    let mut finished = problem.clone();
+    finished
+        .process(crane.as_ref())
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
+    match solve(&problem.stacks, &finished.stacks, crane.as_ref()) {
+        Solution::Exact(moves) | Solution::Heuristic(moves) => {
+            print!("{}", puzzle(problem.stacks, moves))
+        }
+        solution => println!("{:?}", solution),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::crane::{CrateMover9000, CrateMover9001, Rotating};
+    use super::*;
+
+    fn arrangement(stacks: &[&str]) -> Vec<Stack> {
+        stacks
+            .iter()
+            .map(|stack| Stack(stack.chars().collect()))
+            .collect()
+    }
+
+    fn run(start: &[Stack], moves: &[Move], crane: &dyn Crane) -> Vec<Stack> {
+        let mut stacks = start.to_vec();
+        for m in moves {
+            m.check(&stacks).unwrap();
+            m.apply(&mut stacks, crane);
+        }
+        stacks
+    }
+
+    #[test]
+    fn test_exact() {
+        let problem = load(Path::new("src/dec05/example.txt"));
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let mut finished = problem.clone();
+            finished.process(crane).unwrap();
+            match solve(&problem.stacks, &finished.stacks, crane) {
+                Solution::Exact(moves) => {
+                    assert!(moves.len() <= problem.moves.len());
+                    assert_eq!(run(&problem.stacks, &moves, crane), finished.stacks);
+                }
+                solution => panic!("{:?}", solution),
+            }
+        }
+
+        // Top crates first: the rotating crane flips the pair in one move.
+        let start = arrangement(&["AB", ""]);
+        let target = arrangement(&["", "BA"]);
+        match solve(&start, &target, &Rotating) {
+            Solution::Exact(moves) => assert_eq!(moves.len(), 1),
+            solution => panic!("{:?}", solution),
+        }
+        match solve(&start, &target, &CrateMover9001) {
+            Solution::Exact(moves) => assert_eq!(moves.len(), 2),
+            solution => panic!("{:?}", solution),
+        }
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let start = arrangement(&["AB", "C"]);
+        assert!(matches!(
+            solve(&start, &arrangement(&["AB", "D"]), &CrateMover9000),
+            Solution::Unreachable
+        ));
+        assert!(matches!(
+            solve(&start, &arrangement(&["AB", "", "C"]), &CrateMover9000),
+            Solution::Unreachable
+        ));
+        assert!(matches!(
+            solve(
+                &arrangement(&["AB"]),
+                &arrangement(&["BA"]),
+                &CrateMover9000
+            ),
+            Solution::Unreachable
+        ));
+    }
+
+    #[test]
+    fn test_heuristic() {
+        let problem = load(Path::new("src/dec05/testdata.txt"));
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let mut finished = problem.clone();
+            finished.process(crane).unwrap();
+            match solve(&problem.stacks, &finished.stacks, crane) {
+                Solution::Heuristic(moves) => {
+                    assert_eq!(run(&problem.stacks, &moves, crane), finished.stacks);
+                    let puzzle = puzzle(problem.stacks.clone(), moves);
+                    assert!(puzzle.validate().is_empty());
+                }
+                solution => panic!("{:?}", solution),
+            }
+        }
+    }
+}
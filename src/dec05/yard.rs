@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+
+use super::{crane::Crane, Move, Stack};
+use crate::random::Random;
+
+/// A crate in a [Yard], and the root of the block of crates below it in the
+/// tree.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    value: char,
+    priority: u64,
+    /// The number of crates in the block.
+    size: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+    /// Whether the block still has to be turned upside down.
+    flipped: bool,
+}
+
+/// Stacks of crates kept as treaps ordered by position, top crate first, so
+/// a block of any size can be split off, turned over and put on another
+/// stack in logarithmic time.
+#[derive(Debug, Clone)]
+pub(super) struct Yard {
+    nodes: Vec<Node>,
+    stacks: Vec<Option<usize>>,
+    random: Random,
+}
+
+impl Yard {
+    pub(super) fn new(stacks: &[Stack]) -> Self {
+        let mut yard = Yard {
+            nodes: Vec::new(),
+            stacks: Vec::new(),
+            random: Random::new(0x2022_1205),
+        };
+        for stack in stacks {
+            let root = yard.build(stack.0.iter().copied());
+            yard.stacks.push(root);
+        }
+        yard
+    }
+
+    fn build(&mut self, crates: impl Iterator<Item = char>) -> Option<usize> {
+        let mut root = None;
+        for value in crates {
+            self.nodes.push(Node {
+                value,
+                priority: self.random.next_u64(),
+                size: 1,
+                left: None,
+                right: None,
+                flipped: false,
+            });
+            root = self.merge(root, Some(self.nodes.len() - 1));
+        }
+        root
+    }
+
+    fn size(&self, block: Option<usize>) -> usize {
+        block.map_or(0, |idx| self.nodes[idx].size)
+    }
+
+    /// Turns the block at [idx] over one level down the tree.
+    fn push_down(&mut self, idx: usize) {
+        let node = &mut self.nodes[idx];
+        if node.flipped {
+            node.flipped = false;
+            std::mem::swap(&mut node.left, &mut node.right);
+            let (left, right) = (node.left, node.right);
+            for child in [left, right].into_iter().flatten() {
+                self.nodes[child].flipped ^= true;
+            }
+        }
+    }
+
+    fn update(&mut self, idx: usize) {
+        self.nodes[idx].size =
+            1 + self.size(self.nodes[idx].left) + self.size(self.nodes[idx].right);
+    }
+
+    /// Splits [block] into its top [count] crates and the rest.
+    fn split(&mut self, block: Option<usize>, count: usize) -> (Option<usize>, Option<usize>) {
+        let Some(idx) = block else {
+            return (None, None);
+        };
+        self.push_down(idx);
+        let left_size = self.size(self.nodes[idx].left);
+        if count <= left_size {
+            let (top, rest) = self.split(self.nodes[idx].left, count);
+            self.nodes[idx].left = rest;
+            self.update(idx);
+            (top, Some(idx))
+        } else {
+            let (top, rest) = self.split(self.nodes[idx].right, count - left_size - 1);
+            self.nodes[idx].right = top;
+            self.update(idx);
+            (Some(idx), rest)
+        }
+    }
+
+    /// Puts [top] on top of [bottom].
+    fn merge(&mut self, top: Option<usize>, bottom: Option<usize>) -> Option<usize> {
+        match (top, bottom) {
+            (None, block) | (block, None) => block,
+            (Some(top), Some(bottom)) => {
+                if self.nodes[top].priority > self.nodes[bottom].priority {
+                    self.push_down(top);
+                    self.nodes[top].right = self.merge(self.nodes[top].right, Some(bottom));
+                    self.update(top);
+                    Some(top)
+                } else {
+                    self.push_down(bottom);
+                    self.nodes[bottom].left = self.merge(Some(top), self.nodes[bottom].left);
+                    self.update(bottom);
+                    Some(bottom)
+                }
+            }
+        }
+    }
+
+    /// The crates in [block], top first.
+    fn crates(&mut self, block: Option<usize>, crates: &mut VecDeque<char>) {
+        if let Some(idx) = block {
+            self.push_down(idx);
+            self.crates(self.nodes[idx].left, crates);
+            crates.push_back(self.nodes[idx].value);
+            self.crates(self.nodes[idx].right, crates);
+        }
+    }
+
+    /// Replaces the crates in [block], top first, with [crates].
+    fn relabel(&mut self, block: Option<usize>, crates: &mut impl Iterator<Item = char>) {
+        if let Some(idx) = block {
+            self.push_down(idx);
+            self.relabel(self.nodes[idx].left, crates);
+            self.nodes[idx].value = crates.next().unwrap();
+            self.relabel(self.nodes[idx].right, crates);
+        }
+    }
+
+    pub(super) fn heights(&self) -> Vec<usize> {
+        self.stacks.iter().map(|&stack| self.size(stack)).collect()
+    }
+
+    /// Does the same as [Move::apply], without the cost. Cranes that say how
+    /// they leave a whole block move it in one go; the rest lift by lift, as
+    /// do moves back onto the same stack, where each lift is put straight
+    /// back.
+    pub(super) fn apply(&mut self, m: &Move, crane: &dyn Crane) {
+        let (from, to) = m.indices();
+        match crane.flips().filter(|_| from != to) {
+            Some(flips) => {
+                let (block, rest) = self.split(self.stacks[from], m.count);
+                self.stacks[from] = rest;
+                if let (true, Some(idx)) = (flips, block) {
+                    self.nodes[idx].flipped ^= true;
+                }
+                self.stacks[to] = self.merge(block, self.stacks[to]);
+            }
+            None => {
+                for size in crane.lifts(m.count) {
+                    let (block, rest) = self.split(self.stacks[from], size);
+                    self.stacks[from] = rest;
+                    let mut crates = VecDeque::new();
+                    self.crates(block, &mut crates);
+                    crane.set_down(crates.make_contiguous());
+                    self.relabel(block, &mut crates.into_iter());
+                    self.stacks[to] = self.merge(block, self.stacks[to]);
+                }
+            }
+        }
+    }
+
+    pub(super) fn stacks(&mut self) -> Vec<Stack> {
+        (0..self.stacks.len())
+            .map(|stack| {
+                let mut crates = VecDeque::new();
+                self.crates(self.stacks[stack], &mut crates);
+                Stack(crates)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::super::{
+        crane::{Capacity, CrateMover9000, CrateMover9001, Rotating},
+        load,
+    };
+    use super::*;
+
+    #[test]
+    fn test_matches_stacks() {
+        let problem = load(Path::new("src/dec05/testdata.txt"));
+        let cranes: [&dyn Crane; 4] = [
+            &CrateMover9000,
+            &CrateMover9001,
+            &Rotating,
+            &Capacity::new(4),
+        ];
+        for crane in cranes {
+            let mut stacks = problem.stacks.clone();
+            let mut yard = Yard::new(&stacks);
+            for m in &problem.moves {
+                m.apply(&mut stacks, crane);
+                yard.apply(m, crane);
+                assert_eq!(
+                    yard.heights(),
+                    stacks.iter().map(|stack| stack.0.len()).collect::<Vec<_>>()
+                );
+            }
+            assert_eq!(yard.stacks(), stacks);
+        }
+    }
+
+    #[test]
+    fn test_same_stack() {
+        let stacks = vec![Stack("ABCD".chars().collect())];
+        let m = Move {
+            count: 3,
+            from: 1,
+            to: 1,
+            line: 0,
+        };
+        for (crane, after) in [
+            (&CrateMover9000 as &dyn Crane, "ABCD"),
+            (&CrateMover9001, "ABCD"),
+            (&Rotating, "CBAD"),
+        ] {
+            let mut yard = Yard::new(&stacks);
+            yard.apply(&m, crane);
+            assert_eq!(yard.stacks(), vec![Stack(after.chars().collect())]);
+        }
+    }
+}
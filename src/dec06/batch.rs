@@ -0,0 +1,167 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use super::{line, scan, Markers};
+
+/// What decoding one stream found. A start-of-message marker always has a
+/// start-of-packet marker inside it, so there's no message-only case.
+#[derive(Debug, PartialEq, Eq)]
+enum Decoded {
+    NoMarker,
+    PacketOnly { packet: usize },
+    Complete { packet: usize, message: usize },
+}
+
+impl From<Markers> for Decoded {
+    fn from(markers: Markers) -> Self {
+        match (markers.packet, markers.message) {
+            (Some(packet), Some(message)) => Decoded::Complete { packet, message },
+            (Some(packet), None) => Decoded::PacketOnly { packet },
+            _ => Decoded::NoMarker,
+        }
+    }
+}
+
+/// Decodes [stream], which holds a single stream without its line ending.
+fn decode(stream: &[u8]) -> Decoded {
+    // Reading from a slice can't fail.
+    scan(stream).unwrap().into()
+}
+
+/// Applies [f] to every item, sharing them out between as many threads as
+/// there are cores. The results are in the same order as [items].
+fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(idx) {
+                            Some(item) => done.push((idx, f(item))),
+                            None => return done,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|&(idx, _)| idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Decodes each of [streams] in parallel.
+fn decode_batch(streams: &[&[u8]]) -> Vec<Decoded> {
+    parallel_map(streams, |stream| decode(stream))
+}
+
+/// Decodes every line of the file at [path] as its own stream.
+fn decode_file(path: &Path) -> io::Result<Vec<Decoded>> {
+    let input = fs::read(path)?;
+    let mut bytes = input.iter().map(|&byte| Ok(byte)).peekable();
+    let mut streams = Vec::new();
+    // A final newline ends the last stream rather than starting another.
+    while bytes.peek().is_some() {
+        streams.push(line(&mut bytes).collect::<io::Result<Vec<u8>>>()?);
+    }
+    let streams: Vec<&[u8]> = streams.iter().map(Vec::as_slice).collect();
+    Ok(decode_batch(&streams))
+}
+
+/// Decodes every line of every file in [dir], sorted by file name.
+fn decode_dir(dir: &Path) -> io::Result<Vec<(PathBuf, io::Result<Vec<Decoded>>)>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let decoded = decode_file(&path);
+            (path, decoded)
+        })
+        .collect())
+}
+
+/// Prints what every capture file in [dir] decodes to, a line per stream.
+#[allow(dead_code)]
+pub fn report(dir: &Path) {
+    for (path, decoded) in decode_dir(dir).unwrap() {
+        match decoded {
+            Ok(streams) => {
+                for (line, decoded) in streams.iter().enumerate() {
+                    println!("{}:{}: {:?}", path.display(), line + 1, decoded);
+                }
+            }
+            Err(err) => println!("{}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_batch() {
+        let input = fs::read_to_string("src/dec06/example.txt").unwrap();
+        let streams: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let decoded = decode_batch(&streams);
+        assert_eq!(decoded.len(), 5);
+        assert_eq!(
+            decoded[0],
+            Decoded::Complete {
+                packet: 7,
+                message: 19
+            }
+        );
+        assert_eq!(
+            decoded,
+            decode_file(Path::new("src/dec06/example.txt")).unwrap()
+        );
+        assert!(decode_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_decode_dir() {
+        let decoded = decode_dir(Path::new("src/dec06/captures")).unwrap();
+        let names: Vec<_> = decoded
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(
+            decoded[0].1.as_ref().unwrap(),
+            &vec![
+                Decoded::NoMarker,
+                Decoded::PacketOnly { packet: 4 },
+                Decoded::NoMarker,
+                Decoded::Complete {
+                    packet: 4,
+                    message: 14
+                },
+            ]
+        );
+        assert_eq!(decoded[1].1.as_ref().unwrap().len(), 5);
+        // Written with CRLF line endings, which aren't part of the streams.
+        assert_eq!(
+            decoded[2].1.as_ref().unwrap(),
+            &vec![Decoded::NoMarker, Decoded::PacketOnly { packet: 4 }]
+        );
+    }
+}
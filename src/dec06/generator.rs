@@ -0,0 +1,398 @@
+use std::fmt::Display;
+
+use super::{MESSAGE_LEN, PACKET_LEN};
+use crate::random::Random;
+
+/// Where the first markers of a generated stream should end, counting bytes
+/// from 1, and what it's made of.
+#[derive(Debug, Clone)]
+struct Layout {
+    alphabet: Vec<u8>,
+    packet_len: usize,
+    message_len: usize,
+    packet_end: usize,
+    message_end: usize,
+    /// The length of the whole stream.
+    len: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LayoutError {
+    /// Windows must be at least two bytes long, and a start-of-packet one
+    /// no longer than a start-of-message one.
+    BadWindows {
+        packet_len: usize,
+        message_len: usize,
+    },
+    /// A marker needs as many different symbols as it is long.
+    AlphabetTooSmall { needed: usize, available: usize },
+    /// A marker can't end before a whole window has gone by.
+    MarkerTooEarly { end: usize, len: usize },
+    /// The start of a start-of-message marker is always a start-of-packet
+    /// marker, so the first one can't end any later than [latest].
+    PacketTooLate { end: usize, latest: usize },
+    /// With windows the same length both markers are the same window.
+    PacketTooEarly { end: usize, earliest: usize },
+    /// The stream ends before the start-of-message marker does.
+    StreamTooShort { len: usize, message_end: usize },
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutError::BadWindows {
+                packet_len,
+                message_len,
+            } => write!(
+                f,
+                "can't look for markers of lengths {} and {}",
+                packet_len, message_len
+            ),
+            LayoutError::AlphabetTooSmall { needed, available } => write!(
+                f,
+                "markers need {} different symbols but the alphabet has {}",
+                needed, available
+            ),
+            LayoutError::MarkerTooEarly { end, len } => {
+                write!(f, "a marker of length {} can't end at {}", len, end)
+            }
+            LayoutError::PacketTooLate { end, latest } => write!(
+                f,
+                "the start-of-packet marker can't end at {}, later than {}",
+                end, latest
+            ),
+            LayoutError::PacketTooEarly { end, earliest } => write!(
+                f,
+                "the start-of-packet marker can't end at {}, earlier than {}",
+                end, earliest
+            ),
+            LayoutError::StreamTooShort { len, message_end } => {
+                write!(f, "a stream of length {} ends before {}", len, message_end)
+            }
+        }
+    }
+}
+
+/// A byte from [alphabet] that isn't in [used].
+fn pick_new(random: &mut Random, alphabet: &[u8], used: &[u8]) -> u8 {
+    let unused: Vec<u8> = alphabet
+        .iter()
+        .filter(|byte| !used.contains(byte))
+        .copied()
+        .collect();
+    random.pick(&unused)
+}
+
+/// Adds random bytes to [stream] until it is [len] long, never finishing a
+/// window of [window_len] distinct bytes.
+fn fill(random: &mut Random, stream: &mut Vec<u8>, len: usize, window_len: usize, alphabet: &[u8]) {
+    while stream.len() < len {
+        let recent = &stream[stream.len().saturating_sub(window_len - 1)..];
+        let byte = if recent.len() == window_len - 1 && distinct(recent) {
+            random.pick(recent)
+        } else {
+            random.pick(alphabet)
+        };
+        stream.push(byte);
+    }
+}
+
+fn distinct(window: &[u8]) -> bool {
+    window
+        .iter()
+        .enumerate()
+        .all(|(idx, byte)| !window[idx + 1..].contains(byte))
+}
+
+impl Layout {
+    fn check(&self, alphabet: &[u8]) -> Result<(), LayoutError> {
+        if self.packet_len < 2 || self.packet_len > self.message_len {
+            return Err(LayoutError::BadWindows {
+                packet_len: self.packet_len,
+                message_len: self.message_len,
+            });
+        }
+        let available = alphabet.len();
+        let needed = self.packet_len.max(self.message_len);
+        if available < needed {
+            return Err(LayoutError::AlphabetTooSmall { needed, available });
+        }
+        for (end, len) in [
+            (self.packet_end, self.packet_len),
+            (self.message_end, self.message_len),
+        ] {
+            if end < len {
+                return Err(LayoutError::MarkerTooEarly { end, len });
+            }
+        }
+        let latest = self.message_end - self.message_len + self.packet_len;
+        if self.packet_end > latest {
+            return Err(LayoutError::PacketTooLate {
+                end: self.packet_end,
+                latest,
+            });
+        }
+        if self.packet_len == self.message_len && self.packet_end < self.message_end {
+            return Err(LayoutError::PacketTooEarly {
+                end: self.packet_end,
+                earliest: self.message_end,
+            });
+        }
+        if self.len < self.message_end {
+            return Err(LayoutError::StreamTooShort {
+                len: self.len,
+                message_end: self.message_end,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Generates a random stream laid out by [layout] from [seed].
+///
+/// Each marker is a run of distinct bytes that starts with the byte just
+/// before it, so no window ending inside it is a marker. Before the
+/// start-of-packet marker and between the two, every window that would be
+/// a marker has its last byte swapped for one already in it.
+fn generate(layout: &Layout, seed: u64) -> Result<Vec<u8>, LayoutError> {
+    let mut alphabet = layout.alphabet.clone();
+    alphabet.sort_unstable();
+    alphabet.dedup();
+    layout.check(&alphabet)?;
+    let (packet_len, message_len) = (layout.packet_len, layout.message_len);
+    let packet_start = layout.packet_end - packet_len;
+    let message_start = layout.message_end - message_len;
+    let mut random = Random::new(seed);
+
+    let mut stream = Vec::with_capacity(layout.len);
+    fill(
+        &mut random,
+        &mut stream,
+        packet_start,
+        packet_len,
+        &alphabet,
+    );
+    let mut packet = match stream.last() {
+        Some(&byte) => vec![byte],
+        None => vec![random.pick(&alphabet)],
+    };
+    while packet.len() < packet_len {
+        packet.push(pick_new(&mut random, &alphabet, &packet));
+    }
+
+    let mut message = if layout.packet_end <= message_start {
+        stream.extend(&packet);
+        fill(
+            &mut random,
+            &mut stream,
+            message_start,
+            message_len,
+            &alphabet,
+        );
+        vec![*stream.last().unwrap()]
+    } else {
+        // The markers overlap, so the message starts partway through the
+        // packet. Windows ending just before it that start among the first
+        // [before] bytes of the packet have a repeat as long as the message
+        // holds the last of those too, far enough from its end.
+        let before = message_start - packet_start;
+        stream.extend(&packet[..before]);
+        let mut message = packet[before..].to_vec();
+        if before > 0 {
+            let mut rest = vec![packet[before - 1]];
+            while rest.len() < message_len - message.len() {
+                let used = [&message[..], &rest[..]].concat();
+                rest.push(pick_new(&mut random, &alphabet, &used));
+            }
+            let slot = random.below(message_len - packet_len);
+            rest.swap(0, slot);
+            message.extend(rest);
+        }
+        message
+    };
+    while message.len() < message_len {
+        message.push(pick_new(&mut random, &alphabet, &message));
+    }
+    stream.extend(message);
+
+    while stream.len() < layout.len {
+        stream.push(random.pick(&alphabet));
+    }
+    Ok(stream)
+}
+
+/// A stream of [len] bytes whose first marker of [window_len] distinct bytes
+/// ends with its last byte. Cycles through one symbol fewer than a marker
+/// needs, so every window before it repeats a symbol only at its two ends,
+/// and checking a window for repeats from either end goes all the way in.
+fn adversarial(alphabet: &[u8], window_len: usize, len: usize) -> Result<Vec<u8>, LayoutError> {
+    if alphabet.len() < window_len {
+        return Err(LayoutError::AlphabetTooSmall {
+            needed: window_len,
+            available: alphabet.len(),
+        });
+    }
+    if window_len < 2 {
+        return Err(LayoutError::BadWindows {
+            packet_len: window_len,
+            message_len: window_len,
+        });
+    }
+    if len < window_len {
+        return Err(LayoutError::MarkerTooEarly {
+            end: len,
+            len: window_len,
+        });
+    }
+    let cycle = &alphabet[..window_len - 1];
+    let mut stream: Vec<u8> = cycle.iter().copied().cycle().take(len - 1).collect();
+    stream.push(alphabet[window_len - 1]);
+    Ok(stream)
+}
+
+/// Prints two streams of [len] lowercase letters: a random one whose first
+/// markers end at [packet_end] and [message_end], and an adversarial one.
+#[allow(dead_code)]
+pub fn print_streams(packet_end: usize, message_end: usize, len: usize, seed: u64) {
+    let alphabet: Vec<u8> = (b'a'..=b'z').collect();
+    let layout = Layout {
+        alphabet: alphabet.clone(),
+        packet_len: PACKET_LEN,
+        message_len: MESSAGE_LEN,
+        packet_end,
+        message_end,
+        len,
+    };
+    for stream in [
+        generate(&layout, seed),
+        adversarial(&alphabet, MESSAGE_LEN, len),
+    ] {
+        match stream {
+            Ok(stream) => println!("{}", String::from_utf8(stream).unwrap()),
+            Err(err) => println!("{}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::first_markers;
+    use super::*;
+
+    fn markers(stream: &[u8], lens: &[usize]) -> Vec<Option<usize>> {
+        first_markers(&mut stream.iter().map(|&byte| Ok(byte)), lens).unwrap()
+    }
+
+    fn layout(packet_end: usize, message_end: usize) -> Layout {
+        Layout {
+            alphabet: (b'a'..=b'z').collect(),
+            packet_len: 4,
+            message_len: 14,
+            packet_end,
+            message_end,
+            len: 200,
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        for (packet_end, message_end) in [(4, 14), (7, 19), (20, 30), (30, 40), (85, 95), (4, 150)]
+        {
+            for seed in 0..10 {
+                let layout = layout(packet_end, message_end);
+                let stream = generate(&layout, seed).unwrap();
+                assert_eq!(stream.len(), 200);
+                assert_eq!(
+                    markers(&stream, &[4, 14]),
+                    vec![Some(packet_end), Some(message_end)]
+                );
+            }
+        }
+
+        let small = Layout {
+            alphabet: b"abc".to_vec(),
+            packet_len: 2,
+            message_len: 3,
+            packet_end: 5,
+            message_end: 6,
+            len: 6,
+        };
+        let stream = generate(&small, 1).unwrap();
+        assert_eq!(markers(&stream, &[2, 3]), vec![Some(5), Some(6)]);
+    }
+
+    #[test]
+    fn test_every_layout() {
+        // Alphabets only just big enough leave the least room for error.
+        for packet_len in 2..=4 {
+            for message_len in packet_len..=6 {
+                for message_end in message_len..=16 {
+                    for packet_end in packet_len..=message_end - message_len + packet_len {
+                        let layout = Layout {
+                            alphabet: (b'a'..b'a' + message_len as u8).collect(),
+                            packet_len,
+                            message_len,
+                            packet_end,
+                            message_end,
+                            len: 20,
+                        };
+                        if layout.check(&layout.alphabet).is_err() {
+                            continue;
+                        }
+                        for seed in 0..5 {
+                            let stream = generate(&layout, seed).unwrap();
+                            assert_eq!(
+                                markers(&stream, &[packet_len, message_len]),
+                                vec![Some(packet_end), Some(message_end)],
+                                "{:?}",
+                                layout
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        assert_eq!(
+            generate(&layout(3, 20), 1),
+            Err(LayoutError::MarkerTooEarly { end: 3, len: 4 })
+        );
+        assert_eq!(
+            generate(&layout(15, 20), 1),
+            Err(LayoutError::PacketTooLate {
+                end: 15,
+                latest: 10
+            })
+        );
+        assert_eq!(
+            generate(&layout(10, 201), 1),
+            Err(LayoutError::StreamTooShort {
+                len: 200,
+                message_end: 201
+            })
+        );
+        let mut small = layout(10, 20);
+        small.alphabet = b"abcd".to_vec();
+        assert_eq!(
+            generate(&small, 1),
+            Err(LayoutError::AlphabetTooSmall {
+                needed: 14,
+                available: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_adversarial() {
+        let alphabet: Vec<u8> = (b'a'..=b'z').collect();
+        let stream = adversarial(&alphabet, 14, 1000).unwrap();
+        assert_eq!(markers(&stream, &[14]), vec![Some(1000)]);
+        for window in stream[..999].windows(14) {
+            assert_eq!(window[0], window[13]);
+            assert!(distinct(&window[1..]) && distinct(&window[..13]));
+        }
+    }
+}
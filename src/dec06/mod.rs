@@ -1,16 +1,146 @@
-use std::{collections::HashSet, fs, path::Path};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    iter::{self, Peekable},
+    path::Path,
+};
+
+mod batch;
+mod generator;
+mod segments;
+
+const PACKET_LEN: usize = 4;
+const MESSAGE_LEN: usize = 14;
+
+/// Watches a stream one byte at a time for a window of [len] distinct
+/// bytes, keeping a count of each byte in the window so every step is O(1).
+struct Detector {
+    len: usize,
+    window: VecDeque<u8>,
+    counts: [usize; 256],
+    /// The number of different bytes in the window.
+    distinct: usize,
+    /// The number of bytes seen so far.
+    position: usize,
+}
+
+impl Detector {
+    fn new(len: usize) -> Self {
+        assert!(len > 0, "markers must be at least one byte long");
+        Detector {
+            len,
+            window: VecDeque::with_capacity(len + 1),
+            counts: [0; 256],
+            distinct: 0,
+            position: 0,
+        }
+    }
+
+    /// Feeds in the next byte, returning whether the window ending with it
+    /// is a marker.
+    fn push(&mut self, byte: u8) -> bool {
+        self.position += 1;
+        self.window.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 1 {
+            self.distinct += 1;
+        }
+        if self.window.len() > self.len {
+            let old = self.window.pop_front().unwrap() as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 0 {
+                self.distinct -= 1;
+            }
+        }
+        self.distinct == self.len
+    }
+
+    /// Forgets the window, so the next marker can't overlap the last one.
+    fn clear(&mut self) {
+        self.window.clear();
+        self.counts = [0; 256];
+        self.distinct = 0;
+    }
+}
+
+/// Where the first start-of-packet and start-of-message markers in a stream
+/// end, counting bytes from 1.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Markers {
+    packet: Option<usize>,
+    message: Option<usize>,
+}
+
+/// The bytes of the next line of [bytes], which ends at a `\n` or `\r\n`.
+/// The line ending is read but not yielded.
+fn line<I: Iterator<Item = io::Result<u8>>>(
+    bytes: &mut Peekable<I>,
+) -> impl Iterator<Item = io::Result<u8>> + '_ {
+    iter::from_fn(|| match bytes.next()? {
+        Ok(b'\n') => None,
+        Ok(b'\r') if matches!(bytes.peek(), Some(Ok(b'\n'))) => {
+            bytes.next();
+            None
+        }
+        byte => Some(byte),
+    })
+    .fuse()
+}
+
+/// Reads a line from [bytes] until every length has a marker, returning
+/// where the first marker of [lens] distinct bytes ends for each length.
+/// Nothing past the last marker is read.
+fn first_markers(
+    bytes: &mut impl Iterator<Item = io::Result<u8>>,
+    lens: &[usize],
+) -> io::Result<Vec<Option<usize>>> {
+    let mut detectors: Vec<Detector> = lens.iter().map(|&len| Detector::new(len)).collect();
+    let mut markers = vec![None; lens.len()];
+    while markers.iter().any(Option::is_none) {
+        let Some(byte) = bytes.next() else {
+            break;
+        };
+        let byte = byte?;
+        for (detector, marker) in detectors.iter_mut().zip(markers.iter_mut()) {
+            if marker.is_none() && detector.push(byte) {
+                *marker = Some(detector.position);
+            }
+        }
+    }
+    Ok(markers)
+}
+
+/// Finds both markers in the first line of [reader], which may be any
+/// length as only the bytes in the current windows are kept.
+fn scan(reader: impl BufRead) -> io::Result<Markers> {
+    let mut bytes = reader.bytes().peekable();
+    let markers = first_markers(&mut line(&mut bytes), &[PACKET_LEN, MESSAGE_LEN])?;
+    Ok(Markers {
+        packet: markers[0],
+        message: markers[1],
+    })
+}
+
+fn open(path: &Path) -> BufReader<File> {
+    BufReader::new(File::open(path).expect("file not found"))
+}
+
+fn streams(path: &Path) -> Peekable<io::Bytes<BufReader<File>>> {
+    open(path).bytes().peekable()
+}
 
 fn solve(path: &Path, msg_len: usize) -> Vec<usize> {
-    let input = fs::read_to_string(path).expect("file not found");
+    let mut bytes = streams(path);
     let mut markers = Vec::new();
-    for line in input.lines() {
-        let chars = line.chars().collect::<Vec<char>>();
-        for (idx, window) in chars.windows(msg_len).enumerate() {
-            let set: HashSet<&char> = HashSet::from_iter(window);
-            if set.len() == msg_len {
-                markers.push(idx + msg_len);
-                break;
-            }
+    while bytes.peek().is_some() {
+        let mut stream = line(&mut bytes);
+        if let Some(marker) = first_markers(&mut stream, &[msg_len]).unwrap()[0] {
+            markers.push(marker);
+        }
+        // Skip the rest of the line so the next stream starts afresh.
+        for byte in stream {
+            byte.unwrap();
         }
     }
     markers
@@ -18,12 +148,19 @@ fn solve(path: &Path, msg_len: usize) -> Vec<usize> {
 
 #[allow(dead_code)]
 pub fn star_1() -> Vec<usize> {
-    solve(Path::new("src/dec06/testdata.txt"), 4)
+    solve(Path::new("src/dec06/testdata.txt"), PACKET_LEN)
 }
 
 #[allow(dead_code)]
 pub fn star_2() -> Vec<usize> {
-    solve(Path::new("src/dec06/testdata.txt"), 14)
+    solve(Path::new("src/dec06/testdata.txt"), MESSAGE_LEN)
+}
+
+/// Prints where the markers in the stream on stdin end.
+#[allow(dead_code)]
+pub fn watch_stdin() {
+    let markers = scan(io::stdin().lock()).unwrap();
+    println!("{:?}", markers);
 }
 
 #[cfg(test)]
@@ -39,4 +176,68 @@ mod test {
     fn test_star_2() {
         assert_eq!(star_2(), vec![3605]);
     }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(
+            solve(Path::new("src/dec06/example.txt"), PACKET_LEN),
+            vec![7, 5, 6, 10, 11]
+        );
+        assert_eq!(
+            solve(Path::new("src/dec06/example.txt"), MESSAGE_LEN),
+            vec![19, 23, 23, 29, 26]
+        );
+    }
+
+    #[test]
+    fn test_long_stream() {
+        // A million bytes that never hold a marker, then both at once.
+        let stream = io::repeat(b'a')
+            .take(1_000_000)
+            .chain(&b"bcdefghijklmn\nnot this one"[..]);
+        assert_eq!(
+            scan(BufReader::new(stream)).unwrap(),
+            Markers {
+                packet: Some(1_000_003),
+                message: Some(1_000_013),
+            }
+        );
+        assert_eq!(scan(&b"abcabc"[..]).unwrap(), Markers::default());
+    }
+
+    /// Hands out [bytes], then fails if read any further.
+    struct Probe(&'static [u8]);
+
+    impl Read for Probe {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("read too far"));
+            }
+            let len = buf.len().min(self.0.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_stops_at_markers() {
+        // A stream with no newline that can't be read past the message marker.
+        assert_eq!(
+            scan(BufReader::new(Probe(b"abcdefghijklmn"))).unwrap(),
+            Markers {
+                packet: Some(4),
+                message: Some(14),
+            }
+        );
+        // Line by line decoding still starts each stream at its own line.
+        let mut bytes = "abcdxx\nabcdxx\n".bytes().map(Ok).peekable();
+        let mut stream = line(&mut bytes);
+        assert_eq!(first_markers(&mut stream, &[4]).unwrap(), vec![Some(4)]);
+        assert_eq!(stream.next().unwrap().unwrap(), b'x');
+        // A CRLF line ending is not part of the stream.
+        assert_eq!(scan(&b"abc\r\nxyz"[..]).unwrap(), Markers::default());
+        // A lone one is just another byte.
+        assert_eq!(scan(&b"abc\rxyz"[..]).unwrap().packet, Some(4));
+    }
 }
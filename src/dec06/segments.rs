@@ -0,0 +1,204 @@
+use std::{
+    io::{self, BufRead},
+    ops::Range,
+    path::Path,
+};
+
+use super::{line, open, streams, Detector, MESSAGE_LEN, PACKET_LEN};
+
+/// Yields where every window of [len] distinct bytes in a line, as given by
+/// [super::line], ends, counting bytes from 1, including windows that
+/// overlap.
+struct AllMarkers<I> {
+    bytes: I,
+    detector: Detector,
+    done: bool,
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> AllMarkers<I> {
+    fn new(bytes: I, len: usize) -> Self {
+        AllMarkers {
+            bytes,
+            detector: Detector::new(len),
+            done: false,
+        }
+    }
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> Iterator for AllMarkers<I> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.bytes.next() {
+                None => self.done = true,
+                Some(Ok(byte)) => {
+                    if self.detector.push(byte) {
+                        return Some(Ok(self.detector.position));
+                    }
+                }
+                Some(Err(err)) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A marker and the data after it, as byte ranges counted from 0.
+#[derive(Debug, PartialEq, Eq)]
+struct Segment {
+    marker: Range<usize>,
+    /// Runs up to the start of the next marker, or the end of the line.
+    data: Range<usize>,
+}
+
+/// Splits the first line of [reader] at markers of [len] distinct bytes
+/// that don't overlap, taking the earliest each time. Any bytes before the
+/// first marker aren't in a segment.
+fn segment(reader: impl BufRead, len: usize) -> io::Result<Vec<Segment>> {
+    let mut detector = Detector::new(len);
+    let mut segments: Vec<Segment> = Vec::new();
+    for byte in line(&mut reader.bytes().peekable()) {
+        let byte = byte?;
+        if detector.push(byte) {
+            let end = detector.position;
+            if let Some(last) = segments.last_mut() {
+                last.data.end = end - len;
+            }
+            segments.push(Segment {
+                marker: end - len..end,
+                data: end..end,
+            });
+            detector.clear();
+        }
+    }
+    if let Some(last) = segments.last_mut() {
+        last.data.end = detector.position;
+    }
+    Ok(segments)
+}
+
+/// The first of the longest runs of distinct bytes in the first line of
+/// [reader], counting from 0.
+fn longest_run(reader: impl BufRead) -> io::Result<Range<usize>> {
+    let mut last_seen = [None; 256];
+    let mut start = 0;
+    let mut longest = 0..0;
+    for (idx, byte) in line(&mut reader.bytes().peekable()).enumerate() {
+        let byte = byte?;
+        if let Some(seen) = last_seen[byte as usize] {
+            start = start.max(seen + 1);
+        }
+        last_seen[byte as usize] = Some(idx);
+        if idx + 1 - start > longest.len() {
+            longest = start..idx + 1;
+        }
+    }
+    Ok(longest)
+}
+
+/// Prints how the first stream at [path] splits into packets and messages.
+#[allow(dead_code)]
+pub fn report(path: &Path) {
+    let packets = AllMarkers::new(line(&mut streams(path)), PACKET_LEN).count();
+    println!("{} start-of-packet windows", packets);
+    for (name, len) in [("Packet", PACKET_LEN), ("Message", MESSAGE_LEN)] {
+        let segments = segment(open(path), len).unwrap();
+        for segment in segments {
+            println!(
+                "{} marker at {:?}, {} bytes of data",
+                name,
+                segment.marker,
+                segment.data.len()
+            );
+        }
+    }
+    let run = longest_run(open(path)).unwrap();
+    println!("Longest distinct run: {:?} ({} bytes)", run, run.len());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn all_markers(stream: &str, len: usize) -> Vec<usize> {
+        let mut bytes = stream.bytes().map(Ok).peekable();
+        AllMarkers::new(line(&mut bytes), len)
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn test_all_markers() {
+        assert_eq!(all_markers("aabcdd", 3), vec![4, 5]);
+        assert_eq!(all_markers("abcd\nabcd", 4), vec![4]);
+        assert!(all_markers("aaaa", 2).is_empty());
+        assert!(all_markers("aabc\r\n", 4).is_empty());
+
+        let path = Path::new("src/dec06/testdata.txt");
+        let mut bytes = streams(path);
+        let mut markers = AllMarkers::new(line(&mut bytes), PACKET_LEN);
+        assert_eq!(markers.next().unwrap().unwrap(), 1275);
+        assert!(markers.all(|marker| marker.unwrap() > 1275));
+    }
+
+    #[test]
+    fn test_segment() {
+        assert_eq!(
+            segment(&b"aabcabcdefgaa\n"[..], 3).unwrap(),
+            vec![
+                Segment {
+                    marker: 1..4,
+                    data: 4..4,
+                },
+                Segment {
+                    marker: 4..7,
+                    data: 7..7,
+                },
+                Segment {
+                    marker: 7..10,
+                    data: 10..13,
+                },
+            ]
+        );
+        assert_eq!(
+            segment(&b"abcaabbccabd"[..], 3).unwrap(),
+            vec![
+                Segment {
+                    marker: 0..3,
+                    data: 3..8,
+                },
+                Segment {
+                    marker: 8..11,
+                    data: 11..12,
+                },
+            ]
+        );
+        assert!(segment(&b"aaaa"[..], 2).unwrap().is_empty());
+        assert_eq!(
+            segment(&b"abab\r\n"[..], 2).unwrap(),
+            vec![
+                Segment {
+                    marker: 0..2,
+                    data: 2..2,
+                },
+                Segment {
+                    marker: 2..4,
+                    data: 4..4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_longest_run() {
+        assert_eq!(longest_run(&b"abcabcbb"[..]).unwrap(), 0..3);
+        assert_eq!(longest_run(&b"pwwkew"[..]).unwrap(), 2..5);
+        assert_eq!(longest_run(&b"abba\nabcdefg"[..]).unwrap(), 0..2);
+        assert_eq!(longest_run(&b"ab\r\n"[..]).unwrap(), 0..2);
+        assert_eq!(longest_run(&b""[..]).unwrap(), 0..0);
+    }
+}
@@ -1,65 +1,174 @@
-use std::{
-    collections::{HashMap, VecDeque},
-    ops::AddAssign,
-    path::Path,
-};
+use std::{fmt::Display, path::Path};
 
-fn load(path: &Path) -> HashMap<String, u64> {
-    let input = std::fs::read_to_string(path).expect("file not found");
-    let mut dir_map = HashMap::new();
-    let mut curr_path: VecDeque<&str> = VecDeque::new();
-    let lines = input.lines();
-    for line in lines {
-        let parts = line.split(' ').collect::<Vec<&str>>();
-        let first_part = parts[0];
-        match first_part.chars().next().unwrap() {
-            '$' => {
-                let operation = parts[1];
-                match operation {
-                    "cd" => {
-                        let dir = parts[2];
-                        if dir == ".." {
-                            curr_path.pop_back();
-                        } else {
-                            curr_path.push_back(dir);
-                        }
+use self::tree::{Kind, Tree, ROOT};
+
+mod tree;
+
+/// Why a transcript doesn't make sense.
+#[derive(Debug, PartialEq, Eq)]
+enum ShellError {
+    /// A command other than `cd` or `ls`.
+    UnknownCommand(String),
+    /// A line that isn't a command or a listing entry.
+    Malformed(String),
+    /// Output that doesn't follow an `ls`.
+    UnexpectedOutput(String),
+    /// A `cd` into a directory that hasn't been listed.
+    NoSuchDir(String),
+    /// A `cd` into a file.
+    NotADir(String),
+    /// An entry listed again with a different size or kind.
+    Conflict(String),
+}
+
+impl Display for ShellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShellError::UnknownCommand(command) => write!(f, "unknown command: {:?}", command),
+            ShellError::Malformed(line) => write!(f, "malformed line: {:?}", line),
+            ShellError::UnexpectedOutput(line) => write!(f, "output without ls: {:?}", line),
+            ShellError::NoSuchDir(path) => write!(f, "no such directory: {}", path),
+            ShellError::NotADir(path) => write!(f, "not a directory: {}", path),
+            ShellError::Conflict(path) => write!(f, "listed differently before: {}", path),
+        }
+    }
+}
+
+/// Replays a transcript, building up the filesystem it has seen.
+struct Shell {
+    tree: Tree,
+    cwd: usize,
+    /// Whether the last command was `ls`, so its output can follow.
+    listing: bool,
+}
+
+impl Shell {
+    fn new() -> Self {
+        Shell {
+            tree: Tree::new(),
+            cwd: ROOT,
+            listing: false,
+        }
+    }
+
+    /// The directory [target] refers to, from the current one. Only
+    /// directories already listed can be found.
+    fn resolve(&self, target: &str) -> Result<usize, ShellError> {
+        let (mut dir, relative) = match target.strip_prefix('/') {
+            Some(relative) => (ROOT, relative),
+            None => (self.cwd, target),
+        };
+        for name in relative.split('/') {
+            dir = match name {
+                "" | "." => dir,
+                // As in a real shell, the root is its own parent.
+                ".." => self.tree.nodes[dir].parent.unwrap_or(ROOT),
+                _ => match self.tree.child(dir, name) {
+                    Some(child) if self.tree.is_dir(child) => child,
+                    Some(child) => return Err(ShellError::NotADir(self.tree.path(child))),
+                    None => {
+                        let path = self.tree.path(dir);
+                        let path = path.trim_end_matches('/');
+                        return Err(ShellError::NoSuchDir(format!("{}/{}", path, name)));
                     }
-                    "ls" => {}
-                    _ => {}
-                }
+                },
             }
-            '0'..='9' => {
-                let size: u64 = first_part.parse().unwrap();
-                let mut absolute_dir = String::new();
-                for dir in curr_path.iter() {
-                    absolute_dir += dir;
-                    dir_map
-                        .entry(absolute_dir.clone())
-                        .or_insert(0)
-                        .add_assign(size);
+        }
+        Ok(dir)
+    }
+
+    /// Adds an entry from a listing of the current directory, unless it's
+    /// already there from an earlier listing.
+    fn list(&mut self, name: &str, kind: Kind) -> Result<(), ShellError> {
+        match self.tree.child(self.cwd, name) {
+            Some(existing) => {
+                let same = match (&self.tree.nodes[existing].kind, &kind) {
+                    (Kind::Dir(_), Kind::Dir(_)) => true,
+                    (Kind::File(before), Kind::File(now)) => before == now,
+                    _ => false,
+                };
+                if !same {
+                    return Err(ShellError::Conflict(self.tree.path(existing)));
                 }
             }
-            _ => {}
+            None => {
+                self.tree.add(self.cwd, name, kind);
+            }
         }
+        Ok(())
     }
-    dir_map
+
+    fn run(&mut self, line: &str) -> Result<(), ShellError> {
+        if let Some(command) = line.strip_prefix("$ ") {
+            self.listing = false;
+            match command.split_once(' ') {
+                Some(("cd", target)) => self.cwd = self.resolve(target)?,
+                None if command == "ls" => self.listing = true,
+                _ => return Err(ShellError::UnknownCommand(command.to_string())),
+            }
+            return Ok(());
+        }
+        if !self.listing {
+            return Err(ShellError::UnexpectedOutput(line.to_string()));
+        }
+        let malformed = || ShellError::Malformed(line.to_string());
+        let (info, name) = line.split_once(' ').ok_or_else(malformed)?;
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(malformed());
+        }
+        let kind = match info {
+            "dir" => Kind::Dir(Vec::new()),
+            size => Kind::File(size.parse().map_err(|_| malformed())?),
+        };
+        self.list(name, kind)
+    }
+}
+
+/// Rebuilds the filesystem from a shell transcript, or returns the line
+/// number of the first line that doesn't make sense.
+fn parse(input: &str) -> Result<Tree, (usize, ShellError)> {
+    let mut shell = Shell::new();
+    for (line_no, line) in input.lines().enumerate() {
+        shell.run(line).map_err(|err| (line_no + 1, err))?;
+    }
+    Ok(shell.tree)
+}
+
+/// Rebuilds the filesystem from the shell transcript at [path].
+fn load(path: &Path) -> Result<Tree, (usize, ShellError)> {
+    let input = std::fs::read_to_string(path).expect("file not found");
+    parse(&input)
+}
+
+/// The total size of every directory in [tree].
+fn dir_sizes(tree: &Tree) -> Vec<u64> {
+    let sizes = tree.sizes();
+    (0..tree.nodes.len())
+        .filter(|&idx| tree.is_dir(idx))
+        .map(|idx| sizes[idx])
+        .collect()
 }
 
 #[allow(dead_code)]
 pub fn star_1() -> u64 {
-    let dirs = load(Path::new("src/dec07/testdata.txt"));
-    dirs.iter().filter(|(_, size)| **size <= 100_000).map(|(_, size)| size).sum()
+    let tree = load(Path::new("src/dec07/testdata.txt"))
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
+    dir_sizes(&tree)
+        .into_iter()
+        .filter(|size| *size <= 100_000)
+        .sum()
 }
 
 #[allow(dead_code)]
 pub fn star_2() -> u64 {
-    let dirs = load(Path::new("src/dec07/testdata.txt"));
+    let tree = load(Path::new("src/dec07/testdata.txt"))
+        .unwrap_or_else(|(line_no, err)| panic!("line {}: {}", line_no, err));
     const TOTAL_SPACE: u64 = 70_000_000;
     const NEED_SPACE: u64 = 30_000_000;
-    let top_level_dir = dirs["/"];
-    let unused_space = TOTAL_SPACE - top_level_dir;
-    *dirs.values()
-        .filter(|size| **size >= NEED_SPACE - unused_space)
+    let unused_space = TOTAL_SPACE - tree.sizes()[ROOT];
+    dir_sizes(&tree)
+        .into_iter()
+        .filter(|size| *size >= NEED_SPACE - unused_space)
         .min()
         .unwrap()
 }
@@ -77,4 +186,85 @@ mod test {
     fn test_star_2() {
         assert_eq!(star_2(), 2195372);
     }
+
+    #[test]
+    fn test_example() {
+        let tree = load(Path::new("src/dec07/example.txt")).unwrap();
+        let sizes = tree.sizes();
+        let size_of = |path: &str| {
+            let idx = (0..tree.nodes.len())
+                .find(|&idx| tree.path(idx) == path)
+                .unwrap();
+            sizes[idx]
+        };
+        assert_eq!(size_of("/"), 48381165);
+        assert_eq!(size_of("/a"), 94853);
+        assert_eq!(size_of("/a/e"), 584);
+        assert_eq!(size_of("/d"), 24933642);
+        assert_eq!(size_of("/d/k"), 7214296);
+        assert_eq!(tree.nodes.len(), 14);
+    }
+
+    #[test]
+    fn test_shell() {
+        let tree = parse(
+            "$ cd /\n$ ls\ndir a\n1 x\n$ cd a\n$ ls\ndir b\n$ cd b\n$ ls\n2 y\n\
+             $ cd /a/b/../..\n$ ls\n1 x\ndir a\n$ cd ..\n$ cd ./a/b\n$ ls\n2 y\n$ cd /a/./b",
+        )
+        .unwrap();
+        // Listing the root and b twice didn't add anything.
+        assert_eq!(tree.nodes.len(), 5);
+        assert_eq!(tree.sizes()[ROOT], 3);
+
+        let error = |input: &str| parse(input).unwrap_err();
+        assert_eq!(
+            error("$ cd /\n$ cd a"),
+            (2, ShellError::NoSuchDir("/a".to_string()))
+        );
+        assert_eq!(
+            error("$ ls\n1 x\n$ cd x"),
+            (3, ShellError::NotADir("/x".to_string()))
+        );
+        assert_eq!(
+            error("$ ls\n1 x\n$ ls\n2 x"),
+            (4, ShellError::Conflict("/x".to_string()))
+        );
+        assert_eq!(
+            error("$ ls\ndir x\n$ ls\n2 x"),
+            (4, ShellError::Conflict("/x".to_string()))
+        );
+        assert_eq!(
+            error("$ cd /\n1 x"),
+            (2, ShellError::UnexpectedOutput("1 x".to_string()))
+        );
+        assert_eq!(
+            error("$ rm -rf /"),
+            (1, ShellError::UnknownCommand("rm -rf /".to_string()))
+        );
+        assert_eq!(
+            error("$ ls\nbig x"),
+            (2, ShellError::Malformed("big x".to_string()))
+        );
+        assert_eq!(
+            error("$ ls\ndir .."),
+            (2, ShellError::Malformed("dir ..".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut tree = Tree::new();
+        let a = tree.add(ROOT, "a", Kind::Dir(Vec::new()));
+        let ab = tree.add(ROOT, "ab", Kind::Dir(Vec::new()));
+        let bc = tree.add(a, "bc", Kind::File(1));
+        let c = tree.add(ab, "c", Kind::File(2));
+        let empty = tree.add(a, "empty", Kind::Dir(Vec::new()));
+        assert_eq!(tree.path(bc), "/a/bc");
+        assert_eq!(tree.path(c), "/ab/c");
+        assert_eq!(tree.child(a, "bc"), Some(bc));
+        assert_eq!(tree.child(ROOT, "bc"), None);
+        assert_eq!(tree.nodes[empty].parent, Some(a));
+        assert_eq!(tree.sizes(), vec![3, 1, 2, 1, 2, 0]);
+        assert_eq!(dir_sizes(&tree), vec![3, 1, 2, 0]);
+    }
 }
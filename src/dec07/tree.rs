@@ -0,0 +1,103 @@
+/// What a [Node] is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Kind {
+    Dir(Vec<usize>),
+    File(u64),
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Node {
+    pub(super) name: String,
+    /// Only the root has no parent.
+    pub(super) parent: Option<usize>,
+    pub(super) kind: Kind,
+}
+
+/// A filesystem, with every directory and file stored by index. The root
+/// is at index 0, and every node comes after its parent.
+#[derive(Debug, Clone)]
+pub(super) struct Tree {
+    pub(super) nodes: Vec<Node>,
+}
+
+pub(super) const ROOT: usize = 0;
+
+impl Tree {
+    pub(super) fn new() -> Self {
+        Tree {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: Kind::Dir(Vec::new()),
+            }],
+        }
+    }
+
+    pub(super) fn children(&self, dir: usize) -> &[usize] {
+        match &self.nodes[dir].kind {
+            Kind::Dir(children) => children,
+            Kind::File(_) => &[],
+        }
+    }
+
+    /// The entry called [name] in [dir], if there is one.
+    pub(super) fn child(&self, dir: usize, name: &str) -> Option<usize> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].name == name)
+    }
+
+    /// Adds an entry called [name] to [dir], returning its index. Panics if
+    /// [dir] is a file.
+    pub(super) fn add(&mut self, dir: usize, name: &str, kind: Kind) -> usize {
+        let idx = self.nodes.len();
+        match &mut self.nodes[dir].kind {
+            Kind::Dir(children) => children.push(idx),
+            Kind::File(_) => panic!("{} is a file", self.path(dir)),
+        }
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(dir),
+            kind,
+        });
+        idx
+    }
+
+    pub(super) fn is_dir(&self, idx: usize) -> bool {
+        matches!(self.nodes[idx].kind, Kind::Dir(_))
+    }
+
+    /// The total size of every node, counting everything inside each
+    /// directory.
+    pub(super) fn sizes(&self) -> Vec<u64> {
+        let mut sizes: Vec<u64> = self
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                Kind::Dir(_) => 0,
+                Kind::File(size) => size,
+            })
+            .collect();
+        // Children come after their parents, so going backwards finishes
+        // each one before adding it on.
+        for idx in (1..self.nodes.len()).rev() {
+            sizes[self.nodes[idx].parent.unwrap()] += sizes[idx];
+        }
+        sizes
+    }
+
+    /// The absolute path of [idx].
+    pub(super) fn path(&self, idx: usize) -> String {
+        let mut names = Vec::new();
+        let mut next = Some(idx);
+        while let Some(idx) = next {
+            if idx != ROOT {
+                names.push(self.nodes[idx].name.as_str());
+            }
+            next = self.nodes[idx].parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+}
@@ -13,7 +13,16 @@ mod dec12;
 mod dec13;
 mod dec14;
 mod dec15;
+mod random;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, command, path, model] = args.as_slice() {
+        if command == "dec05-replay" {
+            let model = model.parse().expect("expected a CrateMover model");
+            dec05::replay(std::path::Path::new(path), model);
+            return;
+        }
+    }
     println!("{:?}", dec15::star_2());
 }
@@ -0,0 +1,29 @@
+//! A small seeded xorshift generator, for reproducible test inputs and
+//! anything else that needs numbers that only have to look random.
+
+#[derive(Debug, Clone)]
+pub struct Random(u64);
+
+impl Random {
+    /// A generator seeded with [seed]. Seeds that differ only in their
+    /// lowest bit give the same numbers, as a seed of 0 would never change.
+    pub fn new(seed: u64) -> Self {
+        Random(seed | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}